@@ -4,9 +4,96 @@
 // Paradigm(s): Systems Programming, Concurrent Programming
 // ******************
 
+use std::fs::{self, OpenOptions};
 use std::io::{self, Write};
+use std::path::Path;
 
-#[derive(Clone)]
+use chrono::Local;
+
+use csv::{ReaderBuilder, Trim, WriterBuilder};
+
+use serde::{Deserialize, Serialize};
+
+use prettytable::{Cell, Row, Table};
+
+// Annual interest rate, compounded daily over a 365-day year.
+const ANNUAL_INTEREST_RATE: f64 = 0.05;
+
+// Default commission deducted at each step of a multi-hop exchange. None is charged
+// until a rate is agreed; any amount taken is logged as its own COMMISSION line.
+const MULTI_HOP_COMMISSION_RATE: f64 = 0.0;
+
+// Parses a user-entered amount, rejecting text that isn't a finite number ("inf", "nan").
+fn parse_amount(input: &str) -> Option<f64> {
+    input.trim().parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+// Rounds a money amount to whole cents so repeated operations don't drift.
+fn round_money(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+// Formats an amount with a currency symbol, thousands separators and cents, e.g. "$1,234.50".
+// The sign goes before the symbol: "-₱20.00".
+fn format_with_symbol(symbol: &str, amount: f64) -> String {
+    let text = format!("{:.2}", amount.abs());
+    let (whole, cents) = text.split_once('.').unwrap_or((&text, "00"));
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    let sign = if amount < 0.0 && text != "0.00" { "-" } else { "" };
+    format!("{}{}{}.{}", sign, symbol, grouped, cents)
+}
+
+fn format_php_amount(amount: f64) -> String {
+    format_with_symbol("₱", amount)
+}
+
+fn format_usd_amount(amount: f64) -> String {
+    format_with_symbol("$", amount)
+}
+
+fn format_jpy_amount(amount: f64) -> String {
+    format_with_symbol("¥", amount)
+}
+
+fn format_gbp_amount(amount: f64) -> String {
+    format_with_symbol("£", amount)
+}
+
+fn format_eur_amount(amount: f64) -> String {
+    format_with_symbol("€", amount)
+}
+
+// "CN¥" keeps yuan apart from yen, which also uses "¥".
+fn format_cny_amount(amount: f64) -> String {
+    format_with_symbol("CN¥", amount)
+}
+
+// Picks the formatter for a currency code; unknown codes get the code as a prefix.
+fn format_currency_amount(currency: &str, amount: f64) -> String {
+    if currency == "PHP" {
+        format_php_amount(amount)
+    } else if currency == "USD" {
+        format_usd_amount(amount)
+    } else if currency == "JPY" {
+        format_jpy_amount(amount)
+    } else if currency == "GBP" {
+        format_gbp_amount(amount)
+    } else if currency == "EUR" {
+        format_eur_amount(amount)
+    } else if currency == "CNY" {
+        format_cny_amount(amount)
+    } else {
+        format_with_symbol(&format!("{} ", currency), amount)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 struct Account {
     name: String,
     php: f64,
@@ -15,34 +102,90 @@ struct Account {
     gbp: f64,
     eur: f64,
     cny: f64,
+    // Running totals of money deposited and withdrawn, in PHP at the rate of the day.
+    // Saved with the accounts, so they carry over between sessions.
+    total_deposits_lifetime: f64,
+    total_withdrawals_lifetime: f64,
 }
 
+#[derive(Clone)]
 struct ExchangeRate {
     currency: String,
     rate: f64,
 }
 
+// Appends one line per balance or rate change to a text log, e.g.
+// `2024-01-15T10:32:00+08:00 | DEPOSIT | Alice | PHP | +1000.00 | balance=1500.00`.
+struct TransactionLogger {
+    file: fs::File,
+}
+
+impl TransactionLogger {
+    fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(TransactionLogger { file })
+    }
+
+    fn log(&mut self, action: &str, account: &str, currency: &str, change: f64, detail: &str) -> io::Result<()> {
+        let timestamp = Local::now().format("%Y-%m-%dT%H:%M:%S%:z");
+        writeln!(
+            self.file,
+            "{} | {} | {} | {} | {:+.2} | {}",
+            timestamp, action, account, currency, change, detail
+        )?;
+        // Flush every line so nothing is lost if the program crashes.
+        self.file.flush()
+    }
+}
+
 struct BankingSystem {
     accounts: Vec<Account>,
     exchange_rates: Vec<ExchangeRate>,
+    logger: Option<TransactionLogger>,
+    // Fraction kept as commission at each step of a multi-hop exchange.
+    multi_hop_commission_rate: f64,
 }
 
 impl BankingSystem {
     fn new() -> Self {
-        let mut exchange_rates = Vec::new();
-        exchange_rates.push(ExchangeRate { currency: "PHP".to_string(), rate: 1.0 });
-        exchange_rates.push(ExchangeRate { currency: "USD".to_string(), rate: 52.0 });
-        exchange_rates.push(ExchangeRate { currency: "JPY".to_string(), rate: 0.41 });
-        exchange_rates.push(ExchangeRate { currency: "GBP".to_string(), rate: 70.0 });
-        exchange_rates.push(ExchangeRate { currency: "EUR".to_string(), rate: 60.0 });
-        exchange_rates.push(ExchangeRate { currency: "CNY".to_string(), rate: 8.0 });
+        let exchange_rates = vec![
+            ExchangeRate { currency: "PHP".to_string(), rate: 1.0 },
+            ExchangeRate { currency: "USD".to_string(), rate: 52.0 },
+            ExchangeRate { currency: "JPY".to_string(), rate: 0.41 },
+            ExchangeRate { currency: "GBP".to_string(), rate: 70.0 },
+            ExchangeRate { currency: "EUR".to_string(), rate: 60.0 },
+            ExchangeRate { currency: "CNY".to_string(), rate: 8.0 },
+        ];
 
         BankingSystem {
             accounts: Vec::new(),
             exchange_rates,
+            logger: None,
+            multi_hop_commission_rate: MULTI_HOP_COMMISSION_RATE,
         }
     }
 
+    fn with_logger(path: &Path) -> io::Result<Self> {
+        let mut system = BankingSystem::new();
+        system.logger = Some(TransactionLogger::open(path)?);
+        Ok(system)
+    }
+
+    fn log_event(&mut self, action: &str, account: &str, currency: &str, change: f64, detail: &str) {
+        if let Some(logger) = self.logger.as_mut()
+            && let Err(e) = logger.log(action, account, currency, change, detail)
+        {
+            println!("Warning: could not write to the transaction log: {}", e);
+        }
+    }
+
+    // Logs a change to one of an account's balances along with the balance after it.
+    fn log_transaction(&mut self, action: &str, index: usize, currency: &str, change: f64) {
+        let name = self.accounts[index].name.clone();
+        let balance = self.get_balance(&self.accounts[index], currency);
+        self.log_event(action, &name, currency, change, &format!("balance={:.2}", balance));
+    }
+
     fn find_account(&self, name: &str) -> Option<usize> {
         let mut result = None;
         let mut i = 0;
@@ -76,6 +219,7 @@ impl BankingSystem {
     }
 
     fn set_balance(&mut self, index: usize, currency: &str, amount: f64) {
+        let amount = round_money(amount);
         if currency == "PHP" {
             self.accounts[index].php = amount;
         } else if currency == "USD" {
@@ -117,6 +261,11 @@ impl BankingSystem {
         }
     }
 
+    // Converts `amount` of `source` into `target` through their PHP rates, rounded to whole cents.
+    fn convert_amount(&self, amount: f64, source: &str, target: &str) -> f64 {
+        round_money((amount * self.get_exchange_rate(source)) / self.get_exchange_rate(target))
+    }
+
     fn display_main_menu(&self) {
         println!("\n========================================");
         println!("   BANKING & CURRENCY EXCHANGE APP");
@@ -128,6 +277,17 @@ impl BankingSystem {
         println!("[4] Currency Exchange");
         println!("[5] Record Exchange Rates");
         println!("[6] Show Interest Amount");
+        println!("[7] Apply Interest");
+        println!("[8] Import Accounts");
+        println!("[9] Close Account");
+        println!("[10] Show Exchange Rate Matrix");
+        println!("[11] Multi-hop Exchange");
+        println!("[12] Account Summary");
+        println!("[13] What-if Exchange Rates");
+        println!("[14] Export All Accounts to CSV");
+        println!("[15] Account Wealth Ranking");
+        println!("[16] Save Accounts to JSON");
+        println!("[17] Load Accounts from JSON");
         println!("[0] Exit");
         println!("========================================");
     }
@@ -161,12 +321,10 @@ impl BankingSystem {
 
     fn display_all_balances(&self, account: &Account) {
         println!("\nBalances for {}:", account.name);
-        println!("  PHP: {:.2}", account.php);
-        println!("  USD: {:.2}", account.usd);
-        println!("  JPY: {:.2}", account.jpy);
-        println!("  GBP: {:.2}", account.gbp);
-        println!("  EUR: {:.2}", account.eur);
-        println!("  CNY: {:.2}", account.cny);
+        println!("  Net Worth (PHP): {}", format_php_amount(calculate_net_worth(account, self)));
+        for currency in ["PHP", "USD", "JPY", "GBP", "EUR", "CNY"] {
+            println!("  {}: {}", currency, format_currency_amount(currency, self.get_balance(account, currency)));
+        }
     }
 
     fn register_account(&mut self) {
@@ -189,6 +347,8 @@ impl BankingSystem {
                     gbp: 0.0,
                     eur: 0.0,
                     cny: 0.0,
+                    total_deposits_lifetime: 0.0,
+                    total_withdrawals_lifetime: 0.0,
                 };
                 self.accounts.push(account);
                 println!("\nAccount successfully created for {}.", name);
@@ -209,36 +369,78 @@ impl BankingSystem {
         io::stdin().read_line(&mut name).unwrap();
         let name = name.trim().to_string();
 
-        let account_index = self.find_account(&name);
-        if account_index.is_some() {
-            let index = account_index.unwrap();
-            let php_balance = self.accounts[index].php;
-            println!("Current Balance (PHP): {:.2}", php_balance);
-
-            print!("Deposit Amount: ");
+        if let Some(index) = self.find_account(&name) {
+            // Ask which balance to fund; an empty answer keeps the old PHP-only behaviour.
+            println!("Select currency to deposit (Enter for PHP):");
+            self.display_currency_menu();
+            print!("Currency: ");
             io::stdout().flush().unwrap();
             
-            let mut amount_str = String::new();
-            io::stdin().read_line(&mut amount_str).unwrap();
+            let mut currency_choice = String::new();
+            io::stdin().read_line(&mut currency_choice).unwrap();
+            let currency_choice = currency_choice.trim();
             
-            let amount_result = amount_str.trim().parse::<f64>();
-            if amount_result.is_ok() {
-                let amount = amount_result.unwrap();
-                if amount > 0.0 {
-                    self.accounts[index].php = self.accounts[index].php + amount;
-                    let new_balance = self.accounts[index].php;
-                    println!("Updated Balance: {:.2}", new_balance);
+            let currency = if currency_choice.is_empty() {
+                "PHP".to_string()
+            } else {
+                self.get_currency_from_choice(currency_choice)
+            };
+            
+            if !currency.is_empty() {
+                let current_balance = self.get_balance(&self.accounts[index], &currency);
+                println!("Current Balance ({}): {:.2}", currency, current_balance);
+
+                print!("Deposit Amount: ");
+                io::stdout().flush().unwrap();
+                
+                let mut amount_str = String::new();
+                io::stdin().read_line(&mut amount_str).unwrap();
+                
+                if let Some(amount) = parse_amount(&amount_str) {
+                    if amount > 0.0 {
+                        let new_balance = self.deposit(index, &currency, amount);
+                        println!("Updated {} Balance: {:.2}", currency, new_balance);
+                    } else {
+                        println!("Invalid amount.");
+                    }
                 } else {
                     println!("Invalid amount.");
                 }
             } else {
-                println!("Invalid amount.");
+                println!("Invalid currency selection.");
             }
         } else {
             println!("Account not found.");
         }
     }
 
+    // Adds `amount` to one balance, logs it and counts it toward the lifetime deposits.
+    // Returns the new balance.
+    fn deposit(&mut self, index: usize, currency: &str, amount: f64) -> f64 {
+        let current_balance = self.get_balance(&self.accounts[index], currency);
+        self.set_balance(index, currency, current_balance + amount);
+        let new_balance = self.get_balance(&self.accounts[index], currency);
+        self.log_transaction("DEPOSIT", index, currency, new_balance - current_balance);
+        let deposited_php = (new_balance - current_balance) * self.get_exchange_rate(currency);
+        self.accounts[index].total_deposits_lifetime += deposited_php;
+        new_balance
+    }
+
+    // Takes `amount` from one balance, logs it and counts it toward the lifetime withdrawals.
+    // Returns the new balance, or None when the balance is too low.
+    fn withdraw(&mut self, index: usize, currency: &str, amount: f64) -> Option<f64> {
+        let current_balance = self.get_balance(&self.accounts[index], currency);
+        if amount > current_balance {
+            return None;
+        }
+        self.set_balance(index, currency, current_balance - amount);
+        let new_balance = self.get_balance(&self.accounts[index], currency);
+        self.log_transaction("WITHDRAW", index, currency, new_balance - current_balance);
+        let withdrawn_php = (current_balance - new_balance) * self.get_exchange_rate(currency);
+        self.accounts[index].total_withdrawals_lifetime += withdrawn_php;
+        Some(new_balance)
+    }
+
     fn withdraw_amount(&mut self) {
         println!("\n--- Withdraw Amount ---");
         print!("Account Name: ");
@@ -248,9 +450,7 @@ impl BankingSystem {
         io::stdin().read_line(&mut name).unwrap();
         let name = name.trim().to_string();
 
-        let account_index = self.find_account(&name);
-        if account_index.is_some() {
-            let index = account_index.unwrap();
+        if let Some(index) = self.find_account(&name) {
             
             // Display all balances
             self.display_all_balances(&self.accounts[index].clone());
@@ -268,24 +468,18 @@ impl BankingSystem {
             
             let currency = self.get_currency_from_choice(currency_choice);
             
-            if currency != "" {
+            if !currency.is_empty() {
                 print!("Withdraw Amount: ");
                 io::stdout().flush().unwrap();
                 
                 let mut amount_str = String::new();
                 io::stdin().read_line(&mut amount_str).unwrap();
                 
-                let amount_result = amount_str.trim().parse::<f64>();
-                if amount_result.is_ok() {
-                    let amount = amount_result.unwrap();
+                if let Some(amount) = parse_amount(&amount_str) {
                     if amount > 0.0 {
-                        let current_balance = self.get_balance(&self.accounts[index], &currency);
-                        if amount <= current_balance {
-                            self.set_balance(index, &currency, current_balance - amount);
-                            let new_balance = self.get_balance(&self.accounts[index], &currency);
-                            println!("Updated {} Balance: {:.2}", currency, new_balance);
-                        } else {
-                            println!("Error: Insufficient {} funds", currency);
+                        match self.withdraw(index, &currency, amount) {
+                            Some(new_balance) => println!("Updated {} Balance: {:.2}", currency, new_balance),
+                            None => println!("Error: Insufficient {} funds", currency),
                         }
                     } else {
                         println!("Invalid amount.");
@@ -314,7 +508,7 @@ impl BankingSystem {
         
         let currency = self.get_currency_from_choice(choice);
         
-        if currency != "" {
+        if !currency.is_empty() {
             if currency == "PHP" {
                 println!("PHP is the base currency and cannot be modified.");
             } else {
@@ -324,11 +518,11 @@ impl BankingSystem {
                 let mut rate_str = String::new();
                 io::stdin().read_line(&mut rate_str).unwrap();
                 
-                let rate_result = rate_str.trim().parse::<f64>();
-                if rate_result.is_ok() {
-                    let rate = rate_result.unwrap();
+                if let Some(rate) = parse_amount(&rate_str) {
                     if rate > 0.0 {
+                        let previous = self.get_exchange_rate(&currency);
                         self.set_exchange_rate(&currency, rate);
+                        self.log_event("RATE_CHANGE", "-", &currency, rate - previous, &format!("rate={:.2}", rate));
                         println!("\nExchange rate updated: 1 {} = {:.2} PHP", currency, rate);
                     } else {
                         println!("Invalid exchange rate.");
@@ -380,7 +574,7 @@ impl BankingSystem {
                 
                 let source_currency = self.get_currency_from_choice(source_choice);
                 
-                if source_currency == "" {
+                if source_currency.is_empty() {
                     println!("Invalid currency selection.");
                     valid = false;
                 }
@@ -392,11 +586,11 @@ impl BankingSystem {
                     let mut amount_str = String::new();
                     io::stdin().read_line(&mut amount_str).unwrap();
                     
-                    let amount_result = amount_str.trim().parse::<f64>();
+                    let amount_result = parse_amount(&amount_str);
                     let mut source_amount = 0.0;
                     
-                    if amount_result.is_ok() {
-                        source_amount = amount_result.unwrap();
+                    if let Some(amount) = amount_result {
+                        source_amount = amount;
                         if source_amount <= 0.0 {
                             println!("Invalid amount.");
                             valid = false;
@@ -419,7 +613,7 @@ impl BankingSystem {
                         
                         let target_currency = self.get_currency_from_choice(target_choice);
                         
-                        if target_currency == "" {
+                        if target_currency.is_empty() {
                             println!("Invalid currency selection.");
                             valid = false;
                         } else if source_currency == target_currency {
@@ -438,19 +632,49 @@ impl BankingSystem {
                                     println!("Error: Exchange rate not set for selected currencies.");
                                     valid = false;
                                 } else {
-                                    let exchanged_amount = (source_amount * source_rate) / target_rate;
-
-                                    // Update balances
-                                    self.set_balance(index, &source_currency, available_source - source_amount);
+                                    let exchanged_amount = self.convert_amount(source_amount, &source_currency, &target_currency);
                                     let current_target = self.get_balance(&self.accounts[index], &target_currency);
-                                    self.set_balance(index, &target_currency, current_target + exchanged_amount);
-
-                                    println!("\nConverted {:.2} {} -> {:.2} {}", source_amount, source_currency, exchanged_amount, target_currency);
-                                    println!("Updated balances:");
-                                    let src_after = self.get_balance(&self.accounts[index], &source_currency);
-                                    let tgt_after = self.get_balance(&self.accounts[index], &target_currency);
-                                    println!("  {}: {:.2}", source_currency, src_after);
-                                    println!("  {}: {:.2}", target_currency, tgt_after);
+
+                                    // Show the outcome and let the user back out before anything changes.
+                                    println!("\nExchange Preview:");
+                                    println!("  Rate: 1 {} = {:.4} {}", source_currency, source_rate / target_rate, target_currency);
+                                    println!("  {:.2} {} -> {:.2} {}", source_amount, source_currency, exchanged_amount, target_currency);
+                                    println!("Balances after exchange:");
+                                    println!(
+                                        "  {}: {:.2} -> {:.2}",
+                                        source_currency,
+                                        available_source,
+                                        round_money(available_source - source_amount)
+                                    );
+                                    println!(
+                                        "  {}: {:.2} -> {:.2}",
+                                        target_currency,
+                                        current_target,
+                                        round_money(current_target + exchanged_amount)
+                                    );
+
+                                    let mut answer = get_input("Confirm exchange (Y/N): ").to_uppercase();
+                                    while answer != "Y" && answer != "N" {
+                                        println!("Invalid input. Please enter Y or N.");
+                                        answer = get_input("Confirm exchange (Y/N): ").to_uppercase();
+                                    }
+
+                                    if answer == "Y" {
+                                        // Update balances
+                                        self.set_balance(index, &source_currency, available_source - source_amount);
+                                        self.set_balance(index, &target_currency, current_target + exchanged_amount);
+                                        self.log_transaction("EXCHANGE_OUT", index, &source_currency, -source_amount);
+                                        self.log_transaction("EXCHANGE_IN", index, &target_currency, exchanged_amount);
+
+                                        println!("\nConverted {:.2} {} -> {:.2} {}", source_amount, source_currency, exchanged_amount, target_currency);
+                                        println!("Updated balances:");
+                                        let src_after = self.get_balance(&self.accounts[index], &source_currency);
+                                        let tgt_after = self.get_balance(&self.accounts[index], &target_currency);
+                                        println!("  {}: {:.2}", source_currency, src_after);
+                                        println!("  {}: {:.2}", target_currency, tgt_after);
+                                    } else {
+                                        println!("Exchange cancelled. No balances were changed.");
+                                    }
                                 }
                             }
                         }
@@ -479,12 +703,29 @@ impl BankingSystem {
         io::stdin().read_line(&mut name).unwrap();
         let name = name.trim().to_string();
 
-        let account_index = self.find_account(&name);
-        if account_index.is_some() {
-            let index = account_index.unwrap();
-            let php_balance = self.accounts[index].php;
-            println!("Current Balance (PHP): {:.2}", php_balance);
-            println!("Interest Rate: 5%");
+        if let Some(index) = self.find_account(&name) {
+            println!("Project growth on:");
+            println!("[1] PHP balance");
+            println!("[2] Total of all currencies in PHP");
+            let combined = match get_input("Choice [1]: ").as_str() {
+                "" | "1" => false,
+                "2" => true,
+                _ => {
+                    println!("Invalid choice.");
+                    return;
+                }
+            };
+
+            let php_balance = if combined {
+                let total = calculate_net_worth(&self.accounts[index], self);
+                println!("Total Balance (PHP equivalent): {:.2}", total);
+                total
+            } else {
+                let balance = self.accounts[index].php;
+                println!("Current Balance (PHP): {:.2}", balance);
+                balance
+            };
+            println!("Interest Rate: {}%", ANNUAL_INTEREST_RATE * 100.0);
 
             print!("Total Number of Days: ");
             io::stdout().flush().unwrap();
@@ -492,11 +733,9 @@ impl BankingSystem {
             let mut days_str = String::new();
             io::stdin().read_line(&mut days_str).unwrap();
             
-            let days_result = days_str.trim().parse::<u32>();
-            if days_result.is_ok() {
-                let days = days_result.unwrap();
+            if let Ok(days) = days_str.trim().parse::<u32>() {
                 if days > 0 {
-                    let annual_rate = 0.05;
+                    let annual_rate = ANNUAL_INTEREST_RATE;
                     let mut balance = php_balance;
                     
                     println!("\n{}", "-".repeat(50));
@@ -512,6 +751,9 @@ impl BankingSystem {
                     }
                     
                     println!("{}", "-".repeat(50));
+                    if combined {
+                        println!("Note: this projection assumes today's exchange rates stay fixed.");
+                    }
                 } else {
                     println!("Invalid number of days.");
                 }
@@ -522,6 +764,510 @@ impl BankingSystem {
             println!("Account not found.");
         }
     }
+
+    fn transfer_between_currencies_via(
+        &mut self,
+        account_idx: usize,
+        source: &str,
+        amount: f64,
+        via: &str,
+        target: &str,
+    ) -> Result<f64, String> {
+        if source == target {
+            return Err("Source and target currencies are the same.".to_string());
+        }
+        if source == via || via == target {
+            return Err("The intermediate currency must differ from the source and target.".to_string());
+        }
+        if amount <= 0.0 {
+            return Err("Invalid amount.".to_string());
+        }
+
+        let source_rate = self.get_exchange_rate(source);
+        let via_rate = self.get_exchange_rate(via);
+        let target_rate = self.get_exchange_rate(target);
+        if source_rate == 0.0 || via_rate == 0.0 || target_rate == 0.0 {
+            return Err("Exchange rate not set for selected currencies.".to_string());
+        }
+
+        let available_source = self.get_balance(&self.accounts[account_idx], source);
+        if amount > available_source {
+            return Err(format!("Insufficient {} balance. Available: {:.2}", source, available_source));
+        }
+
+        // Keep the starting balances so both steps can be undone together.
+        let original = self.accounts[account_idx].clone();
+        let keep = 1.0 - self.multi_hop_commission_rate;
+
+        // Step 1: source -> via. Only what actually lands in the via balance,
+        // after rounding to cents, is carried into step 2.
+        let via_gross = self.convert_amount(amount, source, via);
+        let via_amount = round_money(via_gross * keep);
+        self.set_balance(account_idx, source, available_source - amount);
+        let via_before = self.get_balance(&self.accounts[account_idx], via);
+        self.set_balance(account_idx, via, via_before + via_amount);
+        let via_credited = round_money(self.get_balance(&self.accounts[account_idx], via) - via_before);
+        if via_credited <= 0.0 {
+            self.accounts[account_idx] = original;
+            return Err(format!("{:.2} {} rounds to 0.00 {}; exchange rolled back.", amount, source, via));
+        }
+
+        // Step 2: via -> target
+        let target_gross = self.convert_amount(via_credited, via, target);
+        let target_amount = round_money(target_gross * keep);
+        if target_amount <= 0.0 {
+            self.accounts[account_idx] = original;
+            return Err(format!("{:.2} {} rounds to 0.00 {}; exchange rolled back.", via_credited, via, target));
+        }
+        let via_balance = self.get_balance(&self.accounts[account_idx], via);
+        self.set_balance(account_idx, via, via_balance - via_credited);
+        let target_balance = self.get_balance(&self.accounts[account_idx], target);
+        self.set_balance(account_idx, target, target_balance + target_amount);
+
+        // Log every leg, including the pass through the via balance and any commission.
+        self.log_transaction("EXCHANGE_OUT", account_idx, source, -amount);
+        self.log_transaction("EXCHANGE_IN", account_idx, via, via_credited);
+        self.log_commission(account_idx, via, via_gross - via_amount);
+        self.log_transaction("EXCHANGE_OUT", account_idx, via, -via_credited);
+        self.log_transaction("EXCHANGE_IN", account_idx, target, target_amount);
+        self.log_commission(account_idx, target, target_gross - target_amount);
+
+        Ok(target_amount)
+    }
+
+    // Logs the commission kept on one step of a multi-hop exchange; nothing when none was taken.
+    fn log_commission(&mut self, account_idx: usize, currency: &str, commission: f64) {
+        let commission = round_money(commission);
+        if commission > 0.0 {
+            let name = self.accounts[account_idx].name.clone();
+            self.log_event("COMMISSION", &name, currency, -commission, &format!("rate={}", self.multi_hop_commission_rate));
+        }
+    }
+
+    fn multi_hop_exchange(&mut self) {
+        println!("\n--- Multi-hop Exchange ---");
+        let name = get_input("Account Name: ");
+
+        if let Some(index) = self.find_account(&name) {
+            self.display_all_balances(&self.accounts[index].clone());
+
+            println!("\nSource Currency Option:");
+            self.display_currency_menu();
+            let source = self.get_currency_from_choice(&get_input("Source Currency: "));
+            if source.is_empty() {
+                println!("Invalid currency selection.");
+                return;
+            }
+
+            let Some(amount) = parse_amount(&get_input("Source Amount: ")) else {
+                println!("Invalid amount.");
+                return;
+            };
+
+            println!("\nIntermediate Currency Options:");
+            self.display_currency_menu();
+            let via = self.get_currency_from_choice(&get_input("Intermediate Currency: "));
+
+            println!("\nExchanged Currency Options:");
+            self.display_currency_menu();
+            let target = self.get_currency_from_choice(&get_input("Exchange Currency: "));
+            if via.is_empty() || target.is_empty() {
+                println!("Invalid currency selection.");
+                return;
+            }
+
+            match self.transfer_between_currencies_via(index, &source, amount, &via, &target) {
+                Ok(credited) => {
+                    println!("\nConverted {:.2} {} -> {} -> {:.2} {}", amount, source, via, credited, target);
+                    println!("Commission: {}% per step", self.multi_hop_commission_rate * 100.0);
+                    self.display_all_balances(&self.accounts[index].clone());
+                }
+                Err(message) => println!("{}", message),
+            }
+        } else {
+            println!("Account not found.");
+        }
+    }
+
+    fn show_exchange_rate_table(&self) {
+        println!("\n--- Exchange Rate Matrix ---");
+        println!("Each cell shows how many units of the column currency equal 1 unit of the row currency.");
+
+        let mut table = Table::new();
+        let mut header = vec![Cell::new("")];
+        for rate in &self.exchange_rates {
+            header.push(Cell::new(&rate.currency).style_spec("b"));
+        }
+        table.add_row(Row::new(header));
+
+        // Cross rates are derived through PHP, the base currency.
+        for from in &self.exchange_rates {
+            let mut cells = vec![Cell::new(&from.currency).style_spec("b")];
+            for to in &self.exchange_rates {
+                let cross = if from.currency == to.currency {
+                    "1.0000".to_string()
+                } else if to.rate == 0.0 {
+                    "N/A".to_string()
+                } else {
+                    format!("{:.4}", from.rate / to.rate)
+                };
+                cells.push(Cell::new(&cross).style_spec("r"));
+            }
+            table.add_row(Row::new(cells));
+        }
+        table.printstd();
+    }
+
+    fn show_account_summary(&self) {
+        println!("\n--- Account Summary ---");
+        let name = get_input("Account Name: ");
+
+        if let Some(index) = self.find_account(&name) {
+            let account = &self.accounts[index];
+            self.display_all_balances(account);
+            println!("\nLifetime Deposits (PHP):    {:.2}", account.total_deposits_lifetime);
+            println!("Lifetime Withdrawals (PHP): {:.2}", account.total_withdrawals_lifetime);
+            println!(
+                "Net Cash Flow (PHP):        {:.2}",
+                account.total_deposits_lifetime - account.total_withdrawals_lifetime
+            );
+        } else {
+            println!("Account not found.");
+        }
+    }
+
+    // Lists every account from richest to poorest by net worth in PHP.
+    // Equal net worths are ordered by account name.
+    fn show_account_ranking_by_net_worth(&self) {
+        println!("\n--- Account Wealth Ranking ---");
+        if self.accounts.is_empty() {
+            println!("No accounts registered.");
+            return;
+        }
+
+        let mut ranking: Vec<(&Account, f64)> = self
+            .accounts
+            .iter()
+            .map(|account| (account, calculate_net_worth(account, self)))
+            .collect();
+        ranking.sort_by(|(a, a_worth), (b, b_worth)| {
+            b_worth
+                .total_cmp(a_worth)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+
+        let mut table = Table::new();
+        table.add_row(Row::new(vec![
+            Cell::new("Rank").style_spec("b"),
+            Cell::new("Name").style_spec("b"),
+            Cell::new("NetWorthPHP").style_spec("b"),
+            Cell::new("TopCurrency").style_spec("b"),
+        ]));
+        for (rank, (account, net_worth)) in ranking.iter().enumerate() {
+            // The currency holding the most value once converted to PHP; the first one wins a tie.
+            let mut top_currency = "-";
+            let mut top_value = 0.0;
+            for rate in &self.exchange_rates {
+                let value = self.get_balance(account, &rate.currency) * rate.rate;
+                if value > top_value {
+                    top_currency = &rate.currency;
+                    top_value = value;
+                }
+            }
+            table.add_row(Row::new(vec![
+                Cell::new(&(rank + 1).to_string()).style_spec("r"),
+                Cell::new(&account.name),
+                Cell::new(&format_php_amount(*net_worth)).style_spec("r"),
+                Cell::new(top_currency),
+            ]));
+        }
+        table.printstd();
+    }
+
+    fn what_if_scenario(&self) {
+        println!("\n--- What-if Exchange Rates ---");
+        let name = get_input("Account Name: ");
+
+        if let Some(index) = self.find_account(&name) {
+            // Work on a copy of the rates so nothing stored is changed.
+            let mut scenario = BankingSystem {
+                accounts: Vec::new(),
+                exchange_rates: self.exchange_rates.clone(),
+                logger: None,
+                multi_hop_commission_rate: self.multi_hop_commission_rate,
+            };
+
+            loop {
+                println!("\nOverride a rate (Enter when done):");
+                self.display_currency_menu();
+                let choice = get_input("Currency: ");
+                if choice.is_empty() {
+                    break;
+                }
+                let currency = self.get_currency_from_choice(&choice);
+                if currency.is_empty() {
+                    println!("Invalid currency selection.");
+                } else if currency == "PHP" {
+                    println!("PHP is the base currency and cannot be modified.");
+                } else {
+                    let prompt = format!("Hypothetical Rate (1 {} = ? PHP): ", currency);
+                    match parse_amount(&get_input(&prompt)) {
+                        Some(rate) if rate > 0.0 => scenario.set_exchange_rate(&currency, rate),
+                        _ => println!("Invalid exchange rate."),
+                    }
+                }
+            }
+
+            let account = &self.accounts[index];
+            let current = calculate_net_worth(account, self);
+            let hypothetical = calculate_net_worth(account, &scenario);
+
+            let mut table = Table::new();
+            table.add_row(Row::new(vec![
+                Cell::new("Currency").style_spec("b"),
+                Cell::new("Balance").style_spec("b"),
+                Cell::new("Current Rate").style_spec("b"),
+                Cell::new("What-if Rate").style_spec("b"),
+            ]));
+            for rate in &self.exchange_rates {
+                table.add_row(Row::new(vec![
+                    Cell::new(&rate.currency),
+                    Cell::new(&format!("{:.2}", self.get_balance(account, &rate.currency))).style_spec("r"),
+                    Cell::new(&format!("{:.4}", rate.rate)).style_spec("r"),
+                    Cell::new(&format!("{:.4}", scenario.get_exchange_rate(&rate.currency))).style_spec("r"),
+                ]));
+            }
+            println!();
+            table.printstd();
+
+            println!("\nNet Worth at current rates (PHP): {:.2}", current);
+            println!("Net Worth at what-if rates (PHP): {:.2}", hypothetical);
+            println!("Difference (PHP):                 {:+.2}", hypothetical - current);
+            println!("Stored rates and balances were not changed.");
+        } else {
+            println!("Account not found.");
+        }
+    }
+
+    fn close_account(&mut self) {
+        println!("\n--- Close Account ---");
+        print!("Account Name: ");
+        io::stdout().flush().unwrap();
+        
+        let mut name = String::new();
+        io::stdin().read_line(&mut name).unwrap();
+        let name = name.trim().to_string();
+
+        if let Some(index) = self.find_account(&name) {
+            let account = self.accounts[index].clone();
+            self.display_all_balances(&account);
+
+            let has_funds = self
+                .exchange_rates
+                .iter()
+                .any(|rate| self.get_balance(&account, &rate.currency) != 0.0);
+            println!("\nTotal (PHP equivalent): {:.2}", calculate_net_worth(&account, self));
+            if has_funds {
+                println!("Warning: This account still holds funds. They will be lost when it is closed.");
+            }
+
+            print!("Type CONFIRM to close this account: ");
+            io::stdout().flush().unwrap();
+            
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer).unwrap();
+            
+            if answer.trim() == "CONFIRM" {
+                self.accounts.remove(index);
+                self.log_event(
+                    "CLOSE",
+                    &account.name,
+                    "PHP",
+                    -calculate_net_worth(&account, self),
+                    "balance=0.00",
+                );
+                println!("Account for {} has been closed.", account.name);
+            } else {
+                println!("Account closure cancelled.");
+            }
+        } else {
+            println!("Account not found.");
+        }
+    }
+
+    // Reads accounts from a CSV whose first row is a header (Name, PHP, USD, JPY, GBP, EUR, CNY).
+    fn import_accounts(&mut self, path: &str) {
+        let mut rdr = match ReaderBuilder::new().has_headers(true).flexible(true).trim(Trim::All).from_path(path) {
+            Ok(rdr) => rdr,
+            Err(e) => {
+                println!("Could not read {}: {}", path, e);
+                return;
+            }
+        };
+
+        let mut created = 0;
+        let mut skipped = 0;
+        for result in rdr.records() {
+            let fields = match result {
+                Ok(fields) => fields,
+                Err(e) => {
+                    println!("Warning: Could not read a row of {}: {}. Skipped.", path, e);
+                    skipped += 1;
+                    continue;
+                }
+            };
+            let name = fields.get(0).unwrap_or("");
+            // Skip blank lines.
+            if name.is_empty() {
+                continue;
+            }
+            if self.find_account(name).is_some() {
+                println!("Warning: Account already exists for {}. Skipped.", name);
+                skipped += 1;
+                continue;
+            }
+
+            // Missing or malformed balances default to 0.0; a negative balance is malformed.
+            let balance = |i: usize, currency: &str| {
+                let amount = fields.get(i).and_then(parse_amount).unwrap_or(0.0);
+                if amount < 0.0 {
+                    println!("Warning: Negative {} balance for {}. Using 0.00.", currency, name);
+                    return 0.0;
+                }
+                round_money(amount)
+            };
+            let mut account = Account {
+                name: name.to_string(),
+                php: balance(1, "PHP"),
+                usd: balance(2, "USD"),
+                jpy: balance(3, "JPY"),
+                gbp: balance(4, "GBP"),
+                eur: balance(5, "EUR"),
+                cny: balance(6, "CNY"),
+                total_deposits_lifetime: 0.0,
+                total_withdrawals_lifetime: 0.0,
+            };
+            // The opening balances count as the account's first deposit.
+            account.total_deposits_lifetime = calculate_net_worth(&account, self);
+            self.accounts.push(account);
+            created += 1;
+        }
+
+        println!("\nImport complete: {} account(s) created, {} skipped.", created, skipped);
+    }
+
+    // Writes every account to a CSV with one column per currency plus its net worth in PHP.
+    // Returns the number of accounts written.
+    fn export_all_accounts_to_csv(&self, path: &Path) -> io::Result<usize> {
+        let mut wtr = WriterBuilder::new()
+            .quote_style(csv::QuoteStyle::Necessary)
+            .from_path(path)?;
+        wtr.write_record(["Name", "PHP", "USD", "JPY", "GBP", "EUR", "CNY", "NetWorthPHP"])?;
+        for account in &self.accounts {
+            let mut row = vec![account.name.clone()];
+            for rate in &self.exchange_rates {
+                row.push(format!("{:.2}", self.get_balance(account, &rate.currency)));
+            }
+            row.push(format!("{:.2}", calculate_net_worth(account, self)));
+            wtr.write_record(&row)?;
+        }
+        wtr.flush()?;
+        Ok(self.accounts.len())
+    }
+
+    // Saves every account, including its lifetime deposit and withdrawal totals, as JSON.
+    // Returns the number of accounts written.
+    fn save_accounts_to_json(&self, path: &Path) -> io::Result<usize> {
+        let text = serde_json::to_string_pretty(&self.accounts).map_err(io::Error::from)?;
+        fs::write(path, text)?;
+        Ok(self.accounts.len())
+    }
+
+    // Replaces the accounts in memory with those saved by `save_accounts_to_json`.
+    // Returns the number of accounts loaded; nothing changes when the file cannot be read.
+    fn load_accounts_from_json(&mut self, path: &Path) -> io::Result<usize> {
+        let text = fs::read_to_string(path)?;
+        let accounts: Vec<Account> = serde_json::from_str(&text).map_err(io::Error::from)?;
+        self.accounts = accounts;
+        Ok(self.accounts.len())
+    }
+
+    fn compute_interest(&self, balance: f64, days: u32) -> f64 {
+        let mut total = balance;
+        let mut day = 1;
+        while day <= days {
+            total += total * (ANNUAL_INTEREST_RATE / 365.0);
+            day += 1;
+        }
+        total - balance
+    }
+
+    fn apply_interest(&mut self) {
+        println!("\n--- Apply Interest ---");
+        print!("Account Name: ");
+        io::stdout().flush().unwrap();
+        
+        let mut name = String::new();
+        io::stdin().read_line(&mut name).unwrap();
+        let name = name.trim().to_string();
+
+        if let Some(index) = self.find_account(&name) {
+            self.display_all_balances(&self.accounts[index].clone());
+            println!();
+
+            println!("Select currency to credit interest to:");
+            self.display_currency_menu();
+            print!("Currency: ");
+            io::stdout().flush().unwrap();
+            
+            let mut currency_choice = String::new();
+            io::stdin().read_line(&mut currency_choice).unwrap();
+            let currency = self.get_currency_from_choice(currency_choice.trim());
+            
+            if !currency.is_empty() {
+                println!("Interest Rate: {}%", ANNUAL_INTEREST_RATE * 100.0);
+                print!("Total Number of Days: ");
+                io::stdout().flush().unwrap();
+                
+                let mut days_str = String::new();
+                io::stdin().read_line(&mut days_str).unwrap();
+                
+                if let Ok(days) = days_str.trim().parse::<u32>() {
+                    if days > 0 {
+                        let before = self.get_balance(&self.accounts[index], &currency);
+                        let interest = self.compute_interest(before, days);
+                        self.set_balance(index, &currency, before + interest);
+                        let after = self.get_balance(&self.accounts[index], &currency);
+                        self.log_transaction("INTEREST", index, &currency, after - before);
+
+                        println!("\nInterest credited for {} days: {:.2} {}", days, interest, currency);
+                        println!("Balance before: {:.2}", before);
+                        println!("Balance after:  {:.2}", after);
+                    } else {
+                        println!("Invalid number of days.");
+                    }
+                } else {
+                    println!("Invalid number of days.");
+                }
+            } else {
+                println!("Invalid currency selection.");
+            }
+        } else {
+            println!("Account not found.");
+        }
+    }
+}
+
+// Total value of every currency balance converted to PHP at the current rates.
+fn calculate_net_worth(account: &Account, system: &BankingSystem) -> f64 {
+    let mut total = 0.0;
+    for rate in &system.exchange_rates {
+        let balance = system.get_balance(account, &rate.currency);
+        if balance != 0.0 {
+            total += balance * rate.rate;
+        }
+    }
+    total
 }
 
 fn get_input(prompt: &str) -> String {
@@ -568,7 +1314,13 @@ where
 }
 
 fn main() {
-    let mut system = BankingSystem::new();
+    let mut system = match BankingSystem::with_logger(Path::new("transactions.log")) {
+        Ok(system) => system,
+        Err(e) => {
+            println!("Warning: could not open transactions.log ({}); transactions will not be logged.", e);
+            BankingSystem::new()
+        }
+    };
 
     println!("\nWelcome to the Banking & Currency Exchange Application!");
 
@@ -590,6 +1342,53 @@ fn main() {
             run_transaction(|| system.record_exchange_rate());
         } else if option == "6" {
             run_transaction(|| system.show_interest_amount());
+        } else if option == "7" {
+            run_transaction(|| system.apply_interest());
+        } else if option == "8" {
+            run_transaction(|| {
+                println!("\n--- Import Accounts ---");
+                let path = get_input("CSV File Path: ");
+                system.import_accounts(&path);
+            });
+        } else if option == "9" {
+            run_transaction(|| system.close_account());
+        } else if option == "10" {
+            run_transaction(|| system.show_exchange_rate_table());
+        } else if option == "11" {
+            run_transaction(|| system.multi_hop_exchange());
+        } else if option == "12" {
+            run_transaction(|| system.show_account_summary());
+        } else if option == "13" {
+            run_transaction(|| system.what_if_scenario());
+        } else if option == "14" {
+            run_transaction(|| {
+                println!("\n--- Export All Accounts to CSV ---");
+                let path = get_input("CSV File Path: ");
+                match system.export_all_accounts_to_csv(Path::new(&path)) {
+                    Ok(count) => println!("\nExported {} account(s) to {}.", count, path),
+                    Err(e) => println!("Could not write {}: {}", path, e),
+                }
+            });
+        } else if option == "15" {
+            run_transaction(|| system.show_account_ranking_by_net_worth());
+        } else if option == "16" {
+            run_transaction(|| {
+                println!("\n--- Save Accounts to JSON ---");
+                let path = get_input("JSON File Path: ");
+                match system.save_accounts_to_json(Path::new(&path)) {
+                    Ok(count) => println!("\nSaved {} account(s) to {}.", count, path),
+                    Err(e) => println!("Could not write {}: {}", path, e),
+                }
+            });
+        } else if option == "17" {
+            run_transaction(|| {
+                println!("\n--- Load Accounts from JSON ---");
+                let path = get_input("JSON File Path: ");
+                match system.load_accounts_from_json(Path::new(&path)) {
+                    Ok(count) => println!("\nLoaded {} account(s) from {}.", count, path),
+                    Err(e) => println!("Could not load {}: {}", path, e),
+                }
+            });
         } else if option == "0" {
             println!("\n========================================");
             println!("Thank you for using our services!");
@@ -601,3 +1400,263 @@ fn main() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Lines of a CSV file with the header kept first and the data rows sorted.
+    fn sorted_lines(path: &Path) -> Vec<String> {
+        let contents = fs::read_to_string(path).unwrap();
+        let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+        lines[1..].sort();
+        lines
+    }
+
+    // A system with one empty account named "Test".
+    fn system_with_account() -> BankingSystem {
+        let mut system = BankingSystem::new();
+        system.accounts.push(Account {
+            name: "Test".to_string(),
+            php: 0.0,
+            usd: 0.0,
+            jpy: 0.0,
+            gbp: 0.0,
+            eur: 0.0,
+            cny: 0.0,
+            total_deposits_lifetime: 0.0,
+            total_withdrawals_lifetime: 0.0,
+        });
+        system
+    }
+
+    #[test]
+    fn round_trip_exchanges_stay_within_a_cent() {
+        let mut system = system_with_account();
+        system.set_balance(0, "PHP", 100_000.0);
+        // The expected PHP balance, kept in whole cents so it cannot drift.
+        let mut expected_cents: i64 = 10_000_000;
+
+        for _ in 0..1000 {
+            let usd = system.convert_amount(10.10, "PHP", "USD");
+            let php = system.accounts[0].php;
+            system.set_balance(0, "PHP", php - 10.10);
+            system.set_balance(0, "USD", system.accounts[0].usd + usd);
+
+            let back = system.convert_amount(usd, "USD", "PHP");
+            let php = system.accounts[0].php;
+            system.set_balance(0, "USD", system.accounts[0].usd - usd);
+            system.set_balance(0, "PHP", php + back);
+
+            // 10.10 PHP buys 0.19 USD, which sells for 9.88 PHP.
+            expected_cents += -1010 + 988;
+            assert!((system.accounts[0].php - expected_cents as f64 / 100.0).abs() < 0.01);
+            assert_eq!(system.accounts[0].usd, 0.0);
+        }
+        assert_eq!(format!("{:.2}", system.accounts[0].php), "99780.00");
+    }
+
+    #[test]
+    fn non_finite_amounts_are_rejected() {
+        for text in ["inf", "-inf", "infinity", "nan", "NaN", " inf\n"] {
+            assert_eq!(parse_amount(text), None, "{:?}", text);
+        }
+        assert_eq!(parse_amount(" 12.50\n"), Some(12.5));
+    }
+
+    #[test]
+    fn net_worth_converts_every_balance_at_the_default_rates() {
+        let mut system = system_with_account();
+        for (currency, amount) in [("PHP", 100.0), ("USD", 10.0), ("JPY", 1000.0), ("GBP", 2.0), ("EUR", 3.0), ("CNY", 50.0)] {
+            system.set_balance(0, currency, amount);
+        }
+        let expected = 100.0 + 10.0 * 52.0 + 1000.0 * 0.41 + 2.0 * 70.0 + 3.0 * 60.0 + 50.0 * 8.0;
+        assert!((calculate_net_worth(&system.accounts[0], &system) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn net_worth_follows_exchange_rate_changes() {
+        let mut system = system_with_account();
+        system.set_balance(0, "PHP", 100.0);
+        system.set_balance(0, "USD", 10.0);
+        assert!((calculate_net_worth(&system.accounts[0], &system) - 620.0).abs() < 1e-9);
+
+        system.set_exchange_rate("USD", 56.0);
+        assert!((calculate_net_worth(&system.accounts[0], &system) - 660.0).abs() < 1e-9);
+    }
+
+    // Small deterministic generator so the property test needs no extra crate.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self) -> u64 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            self.0 >> 33
+        }
+    }
+
+    #[test]
+    fn lifetime_cash_flow_matches_net_worth() {
+        // Holds for deposits and withdrawals alone; exchanges, interest and rate changes move
+        // net worth without any cash flowing in or out.
+        let currencies = ["PHP", "USD", "JPY", "GBP", "EUR", "CNY"];
+        for seed in 0..20 {
+            let mut rng = Lcg(seed);
+            let mut system = system_with_account();
+            for _ in 0..200 {
+                let currency = currencies[(rng.next() % 6) as usize];
+                let amount = (rng.next() % 100_000) as f64 / 100.0 + 0.01;
+                if rng.next().is_multiple_of(3) {
+                    system.withdraw(0, currency, amount);
+                } else {
+                    system.deposit(0, currency, amount);
+                }
+            }
+            let account = &system.accounts[0];
+            let cash_flow = account.total_deposits_lifetime - account.total_withdrawals_lifetime;
+            let net_worth = calculate_net_worth(account, &system);
+            assert!((cash_flow - net_worth).abs() < 1e-6, "seed {}: {} vs {}", seed, cash_flow, net_worth);
+        }
+    }
+
+    #[test]
+    fn saved_accounts_keep_their_lifetime_totals() {
+        let path = std::env::temp_dir().join(format!("mp3_accounts_{}.json", std::process::id()));
+        let mut system = system_with_account();
+        system.deposit(0, "USD", 10.0);
+        system.withdraw(0, "USD", 2.5);
+        system.save_accounts_to_json(&path).unwrap();
+
+        let mut restored = BankingSystem::new();
+        let loaded = restored.load_accounts_from_json(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, 1);
+        let account = &restored.accounts[0];
+        assert_eq!((account.name.as_str(), account.usd), ("Test", 7.5));
+        assert_eq!(account.total_deposits_lifetime, 520.0);
+        assert_eq!(account.total_withdrawals_lifetime, 130.0);
+    }
+
+    #[test]
+    fn imported_balances_count_as_deposits() {
+        let path = std::env::temp_dir().join(format!("mp3_opening_{}.csv", std::process::id()));
+        fs::write(&path, "Name,PHP,USD\nMaria,1000.00,10.00\n").unwrap();
+        let mut system = BankingSystem::new();
+        system.import_accounts(&path.display().to_string());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(system.accounts[0].total_deposits_lifetime, 1520.0);
+        assert_eq!(system.accounts[0].total_withdrawals_lifetime, 0.0);
+    }
+
+    #[test]
+    fn imported_negative_balances_open_at_zero() {
+        let path = std::env::temp_dir().join(format!("mp3_negative_{}.csv", std::process::id()));
+        fs::write(&path, "Name,PHP,USD\nMaria,-1000.00,10.00\n").unwrap();
+        let mut system = BankingSystem::new();
+        system.import_accounts(&path.display().to_string());
+        fs::remove_file(&path).unwrap();
+
+        let account = &system.accounts[0];
+        assert_eq!((account.php, account.usd), (0.0, 10.0));
+        assert_eq!(account.total_deposits_lifetime, 520.0);
+    }
+
+    #[test]
+    fn multi_hop_exchange_logs_every_leg() {
+        let log = std::env::temp_dir().join(format!("mp3_hops_{}.log", std::process::id()));
+        let mut system = BankingSystem::with_logger(&log).unwrap();
+        system.accounts = system_with_account().accounts;
+        system.set_balance(0, "PHP", 1000.0);
+
+        // 520 PHP buys 10.00 USD, which buys 7.43 GBP.
+        let credited = system.transfer_between_currencies_via(0, "PHP", 520.0, "USD", "GBP");
+        let lines: Vec<String> = fs::read_to_string(&log).unwrap().lines().map(str::to_string).collect();
+        fs::remove_file(&log).unwrap();
+
+        assert_eq!(credited, Ok(7.43));
+        let account = &system.accounts[0];
+        assert_eq!((account.php, account.usd, account.gbp), (480.0, 0.0, 7.43));
+        // Action, account, currency and change of each line, without the timestamp.
+        let legs: Vec<String> = lines.iter().map(|l| l.split(" | ").skip(1).take(4).collect::<Vec<_>>().join(" ")).collect();
+        assert_eq!(
+            legs,
+            vec![
+                "EXCHANGE_OUT Test PHP -520.00",
+                "EXCHANGE_IN Test USD +10.00",
+                "EXCHANGE_OUT Test USD -10.00",
+                "EXCHANGE_IN Test GBP +7.43",
+            ]
+        );
+    }
+
+    #[test]
+    fn multi_hop_exchange_deducts_and_logs_commission_at_each_step() {
+        let log = std::env::temp_dir().join(format!("mp3_commission_{}.log", std::process::id()));
+        let mut system = BankingSystem::with_logger(&log).unwrap();
+        system.accounts = system_with_account().accounts;
+        system.multi_hop_commission_rate = 0.01;
+        system.set_balance(0, "PHP", 1000.0);
+
+        // 520 PHP buys 10.00 USD, 9.90 after commission; 9.90 USD buys 7.35 GBP, 7.28 after commission.
+        let credited = system.transfer_between_currencies_via(0, "PHP", 520.0, "USD", "GBP");
+        let lines: Vec<String> = fs::read_to_string(&log).unwrap().lines().map(str::to_string).collect();
+        fs::remove_file(&log).unwrap();
+
+        assert_eq!(credited, Ok(7.28));
+        let account = &system.accounts[0];
+        assert_eq!((account.php, account.usd, account.gbp), (480.0, 0.0, 7.28));
+        let commissions: Vec<String> = lines
+            .iter()
+            .filter(|l| l.contains(" | COMMISSION | "))
+            .map(|l| l.split(" | ").skip(1).collect::<Vec<_>>().join(" "))
+            .collect();
+        assert_eq!(commissions, vec!["COMMISSION Test USD -0.10 rate=0.01", "COMMISSION Test GBP -0.07 rate=0.01"]);
+    }
+
+    #[test]
+    fn multi_hop_exchange_rejects_pointless_or_lossy_routes() {
+        let mut system = system_with_account();
+        system.set_balance(0, "PHP", 100.0);
+        system.set_balance(0, "JPY", 100.0);
+
+        assert!(system.transfer_between_currencies_via(0, "PHP", 10.0, "USD", "PHP").is_err());
+        assert!(system.transfer_between_currencies_via(0, "PHP", 10.0, "PHP", "USD").is_err());
+
+        // 0.01 JPY is worth less than a cent of USD, so step 2 has nothing to convert.
+        let result = system.transfer_between_currencies_via(0, "JPY", 0.01, "USD", "PHP");
+        assert!(result.unwrap_err().contains("rolled back"));
+        let account = &system.accounts[0];
+        assert_eq!((account.php, account.jpy, account.usd), (100.0, 100.0, 0.0));
+    }
+
+    #[test]
+    fn exported_accounts_match_the_imported_file() {
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("mp3_import_{}.csv", std::process::id()));
+        let output = dir.join(format!("mp3_export_{}.csv", std::process::id()));
+        fs::write(
+            &input,
+            "Name,PHP,USD,JPY,GBP,EUR,CNY,NetWorthPHP\n\
+             Maria,1000.00,10.00,0.00,0.00,0.00,0.00,1520.00\n\
+             Juan,0.00,0.00,1000.00,1.50,2.00,100.00,1435.00\n\
+             \"Cruz, Juan\",50.00,0.00,0.00,0.00,0.00,0.00,50.00\n\
+             Name,25.00,0.00,0.00,0.00,0.00,0.00,25.00\n",
+        )
+        .unwrap();
+
+        let mut system = BankingSystem::new();
+        system.import_accounts(&input.display().to_string());
+        let written = system.export_all_accounts_to_csv(&output).unwrap();
+        let (imported, exported) = (sorted_lines(&input), sorted_lines(&output));
+        fs::remove_file(&input).unwrap();
+        fs::remove_file(&output).unwrap();
+
+        assert_eq!(written, 4);
+        assert_eq!(exported, imported);
+        assert_eq!(system.accounts[2].name, "Cruz, Juan");
+        assert_eq!(system.accounts[2].php, 50.0);
+        assert_eq!(system.accounts[3].name, "Name");
+    }
+}
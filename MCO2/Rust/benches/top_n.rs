@@ -0,0 +1,32 @@
+//! Compares `top_n` with a full sort and truncate for the Report 2 case (top 15 of many).
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::hint::black_box;
+
+use dpwh::compute::{top_n, OrdF64};
+
+// Items in a scrambled but repeatable order, as contractors arrive from the CSV.
+fn sample(len: usize) -> Vec<(usize, f64)> {
+    (0..len).map(|i| (i, ((i * 7919) % 10_007) as f64 * 1.5)).collect()
+}
+
+fn sort_and_truncate(mut items: Vec<(usize, f64)>, n: usize) -> Vec<(usize, f64)> {
+    items.sort_by(|a, b| b.1.total_cmp(&a.1));
+    items.truncate(n);
+    items
+}
+
+fn bench_top_n(c: &mut Criterion) {
+    let items = sample(10_000);
+    let mut group = c.benchmark_group("top 15 of 10k");
+    group.bench_function("top_n", |b| {
+        b.iter_batched(|| items.clone(), |items| top_n(items, black_box(15), |item| OrdF64(item.1)), BatchSize::SmallInput)
+    });
+    group.bench_function("sort + truncate", |b| {
+        b.iter_batched(|| items.clone(), |items| sort_and_truncate(items, black_box(15)), BatchSize::SmallInput)
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_top_n);
+criterion_main!(benches);
@@ -0,0 +1,1463 @@
+//! Menu actions and the load/report workflow driven by the binary.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use csv::ReaderBuilder;
+use log::{debug, info, trace, warn};
+use serde_json::{json, Value as JsonValue};
+use prettytable::{Table, Row, Cell};
+
+use crate::cache::{cache_key, cache_path, read_cache, write_cache};
+use crate::manifest::{read_manifest, verify_manifest, write_manifest};
+use crate::error::PipelineError;
+use crate::model::{RawRecord, CleanedRecord, ProcessedRecord, ReportRow, Delimiter, CoordinateConflict, IslandWarning, OutputFormat, ValidationLog};
+use crate::config::{Config, ReportSelection, RecordFilter, ReportSort, parse_filter_list, parse_sort_list, Settings};
+use crate::io::{
+    Progress, check_writable, find_csv_file, resolve_input_files, read_and_merge_csvs, rows_per_file, run_with_timeout, source_name, stream_csv_files, write_csv,
+    write_json, write_processed_json, write_cleaned_json, export_to_sqlite, ask_question,
+};
+use crate::validate::clean_raw_record;
+use crate::transform::{
+    add_derived_fields, flag_negative_delays, detect_duplicate_records, remove_duplicates, reconcile_cross_file_duplicates,
+    detect_coordinate_conflicts, impute_coordinates, apply_filters, search_contractor_records, find_contractors, filter_by_year_range,
+    detect_severe_delays, delay_mean_and_std_dev, SEVERE_DELAY_Z_SCORE, compute_dataset_medians, fill_missing_with_medians, strip_derived_fields,
+};
+use crate::compute::format_number;
+use crate::reports::{
+    generate_report1, generate_report2, generate_report3, generate_report6, generate_report8, generate_report9,
+    generate_summary, write_summary, truncate_cell, print_bar_chart, console_table_format,
+    build_total_row, write_report, write_html_page, generate_contractor_drilldown, risk_flag, apply_report_sort,
+};
+
+// ============================================================================
+// MAIN APPLICATION LOGIC
+// ============================================================================
+
+// Number of matches shown per page by the contractor search.
+pub const SEARCH_PAGE_SIZE: usize = 20;
+
+// Contractor names listed when a drill-down name matches more than one.
+pub const DRILLDOWN_CANDIDATES: usize = 10;
+
+// Rows per page and maximum cell width when viewing a full report.
+pub const REPORT_PAGE_SIZE: usize = 20;
+pub const REPORT_CELL_WIDTH: usize = 40;
+
+// Columns of the processed-data export, one per `ProcessedRecord` field.
+pub const PROCESSED_HEADERS: [&str; 15] = [
+    "Region", "MainIsland", "FundingYear", "ApprovedBudgetForContract", "ContractCost",
+    "StartDate", "ActualCompletionDate", "ProjectLatitude", "ProjectLongitude", "Province",
+    "Contractor", "TypeOfWork", "CostSavings", "CompletionDelayDays", "CoordinatesImputed",
+];
+
+// Columns shown and exported by the contractor search.
+pub const SEARCH_HEADERS: [&str; 8] = ["Contractor", "Region", "Province", "FundingYear", "ApprovedBudget", "ContractCost", "CostSavings", "DelayDays"];
+
+// Timing and record counts for one step of the loading pipeline.
+pub struct StageResult {
+    pub name: String,
+    pub input_count: usize,
+    pub output_count: usize,
+    pub duration: Duration,
+}
+
+// Records a `StageResult` for every stage run while loading the dataset or writing reports,
+// plus the number of records left at each milestone (e.g. raw → valid → filtered).
+#[derive(Default)]
+pub struct Pipeline {
+    pub stages: Vec<StageResult>,
+    pub record_counts: Vec<(&'static str, usize)>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Pipeline { stages: Vec::new(), record_counts: Vec::new() }
+    }
+
+    /// Records a stage that was timed by the caller from `started` until now.
+    pub fn record(&mut self, name: &str, input_count: usize, output_count: usize, started: Instant) {
+        debug!("Stage {}: {} → {} in {:.2?}", name, input_count, output_count, started.elapsed());
+        self.stages.push(StageResult {
+            name: name.to_string(),
+            input_count,
+            output_count,
+            duration: started.elapsed(),
+        });
+    }
+
+    /// Runs one stage, timing it and recording how many items went in and came out.
+    pub fn run_stage<T, F>(&mut self, name: &str, input_count: usize, stage: F) -> Result<Vec<T>, PipelineError>
+    where
+        F: FnOnce() -> Result<Vec<T>, PipelineError>,
+    {
+        let started = Instant::now();
+        let output = stage()?;
+        debug!("Stage {}: {} → {} in {:.2?}", name, input_count, output.len(), started.elapsed());
+        self.stages.push(StageResult {
+            name: name.to_string(),
+            input_count,
+            output_count: output.len(),
+            duration: started.elapsed(),
+        });
+        Ok(output)
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        self.stages.iter().map(|s| s.duration).sum()
+    }
+
+    /// Prints the per-stage timings as a console table under `title`,
+    /// followed by the record counts.
+    pub fn print_timings(&self, title: &str) {
+        let mut table = Table::new();
+        table.set_format(console_table_format());
+        table.add_row(Row::new(
+            ["Stage", "In", "Out", "Time (ms)"].iter().map(|&h| Cell::new(h).style_spec("b")).collect(),
+        ));
+        for stage in &self.stages {
+            table.add_row(Row::new(vec![
+                Cell::new(&stage.name),
+                Cell::new(&stage.input_count.to_string()).style_spec("r"),
+                Cell::new(&stage.output_count.to_string()).style_spec("r"),
+                Cell::new(&format!("{:.2}", stage.duration.as_secs_f64() * 1000.0)).style_spec("r"),
+            ]));
+        }
+        table.add_row(Row::new(vec![
+            Cell::new("Total").style_spec("b"),
+            Cell::new(""),
+            Cell::new(""),
+            Cell::new(&format!("{:.2}", self.total_duration().as_secs_f64() * 1000.0)).style_spec("br"),
+        ]));
+        println!("{}:", title);
+        table.printstd();
+        if !self.record_counts.is_empty() {
+            let counts: Vec<String> = self.record_counts.iter().map(|(label, count)| format!("{} {}", label, count)).collect();
+            println!("Records: {}", counts.join(" → "));
+        }
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let stages: Vec<JsonValue> = self.stages.iter().map(|stage| json!({
+            "name": stage.name,
+            "input_count": stage.input_count,
+            "output_count": stage.output_count,
+            "duration_ms": stage.duration.as_secs_f64() * 1000.0,
+        })).collect();
+        let counts: Vec<JsonValue> = self.record_counts.iter().map(|(label, count)| json!({
+            "stage": label,
+            "records": count,
+        })).collect();
+        json!({
+            "stages": stages,
+            "record_counts": counts,
+            "total_duration_ms": self.total_duration().as_secs_f64() * 1000.0,
+        })
+    }
+}
+
+/// Lists each input file with its row count, followed by the combined total.
+fn print_input_summary(csv_files: &[PathBuf], file_rows: &[usize]) {
+    let total: usize = file_rows.iter().sum();
+    let width = csv_files.iter().map(|p| p.display().to_string().len()).max().unwrap_or(0).max("Total".len());
+    println!("Input files:");
+    for (path, rows) in csv_files.iter().zip(file_rows) {
+        println!("  {:<width$}  {:>8} rows", path.display().to_string(), format_number(*rows as f64, 0), width = width);
+    }
+    println!("  {:<width$}  {:>8} rows ({} file(s))", "Total", format_number(total as f64, 0), csv_files.len(), width = width);
+}
+
+/// Load and process the CSV file
+/// Returns the time and record counts of each stage; with `--timings` they are also printed.
+/// When no row survives cleaning the empty dataset is still stored, and the
+/// returned `EmptyDataset` or `NoValidRecords` error says why.
+pub fn load_file(
+    raw_records: &mut Option<Vec<RawRecord>>,
+    processed_data: &mut Option<Vec<ProcessedRecord>>,
+    settings: &Settings,
+) -> Result<Pipeline, PipelineError> {
+    println!("Processing dataset...");
+    let load_started = Instant::now();
+    let mut pipeline = Pipeline::new();
+    let rules = &settings.validation;
+
+    // Merge the files given with `--input`/`--csv-dir`; otherwise read only the main dataset,
+    // so other files dropped into the data directory cannot change the reports.
+    let csv_files = if !settings.inputs.is_empty() {
+        resolve_input_files(&settings.inputs)?
+    } else {
+        vec![find_csv_file()?]
+    };
+
+    // Reuse the records cached by an earlier run when the files and loading options are unchanged.
+    let cache = if settings.use_cache && !settings.output.dry_run {
+        Some((cache_path(settings)?, cache_key(&csv_files, settings)?))
+    } else {
+        None
+    };
+    if let Some((path, key)) = &cache
+        && let Some(records) = read_cache(path, *key)
+        && confirm_cached_load(settings, records.len())?
+    {
+        *raw_records = None;
+        println!("({} records loaded from the cache; run with --no-cache to re-read the CSV files)\n", records.len());
+        pipeline.record("read cache", csv_files.len(), records.len(), load_started);
+        pipeline.record_counts.push(("cached", records.len()));
+        if settings.timings {
+            pipeline.print_timings("Pipeline timings");
+        }
+        info!("Load completed in {:.2}s", load_started.elapsed().as_secs_f64());
+        *processed_data = Some(records);
+        return Ok(pipeline);
+    }
+
+    // Either stream rows straight into processed records, or read all raw
+    // records first and keep them in memory for later inspection.
+    let mut log = ValidationLog::default();
+    let mut file_rows = Vec::new();
+    let derived = if settings.stream {
+        *raw_records = None;
+        pipeline.run_stage("read/clean/derive (streamed)", csv_files.len(), || {
+            let (files, rules) = (csv_files.clone(), rules.clone());
+            let (processed, stream_log, rows) =
+                run_with_timeout(settings.load_timeout, move || stream_csv_files(&files, &rules))?;
+            log = stream_log;
+            file_rows = rows;
+            Ok(processed)
+        })?
+    } else {
+        let raw_vec = pipeline.run_stage("read", csv_files.len(), || {
+            let files = csv_files.clone();
+            let raw_vec = run_with_timeout(settings.load_timeout, move || read_and_merge_csvs(&files))?;
+            file_rows = rows_per_file(&raw_vec, &csv_files);
+            Ok(raw_vec)
+        })?;
+        info!("Raw records loaded: {} from {} file(s)", raw_vec.len(), csv_files.len());
+        let raw_count = raw_vec.len();
+
+        // Validate and clean every record.
+        // Row numbers restart for every source file so messages point at the right line.
+        let cleaned = pipeline.run_stage("validate/clean", raw_count, || {
+            let mut cleaned = Vec::new();
+            let mut current_file = String::new();
+            let mut row_in_file = 0;
+            let mut progress = Progress::new("Cleaning");
+            for record in raw_vec.iter() {
+                progress.tick();
+                if record.source_file != current_file {
+                    current_file = record.source_file.clone();
+                    row_in_file = 0;
+                }
+                row_in_file += 1;
+                if let Some(clean) = clean_raw_record(record, row_in_file, rules, &mut log) {
+                    cleaned.push(clean);
+                }
+            }
+            progress.finish();
+            Ok(cleaned)
+        })?;
+
+        *raw_records = Some(raw_vec);
+        let cleaned_count = cleaned.len();
+        pipeline.run_stage("derive", cleaned_count, || {
+            Ok(cleaned.into_iter().map(add_derived_fields).collect())
+        })?
+    };
+
+    let raw_count: usize = file_rows.iter().sum();
+    print_input_summary(&csv_files, &file_rows);
+    pipeline.record_counts.push(("raw", raw_count));
+    pipeline.record_counts.push(("valid", derived.len()));
+
+    // Display a summary of validation issues for transparency.
+    let errors = &log.errors;
+    if !errors.is_empty() {
+        warn!("Validation errors detected: {} invalid records", errors.len());
+        for err in errors.iter().take(10) {
+            warn!("{}", err);
+        }
+        if errors.len() > 10 {
+            warn!("  ... and {} more errors (use -v to list all)", errors.len() - 10);
+            for err in errors.iter().skip(10) {
+                debug!("{}", err);
+            }
+        }
+        info!("Valid records: {} out of {}", derived.len(), raw_count);
+    }
+    if !log.island_warnings.is_empty() {
+        warn!("Island mismatches detected: {} records whose MainIsland does not match their Region", log.island_warnings.len());
+        write_island_warnings(&log.island_warnings, settings)?;
+    }
+
+    // Stop here when nothing survived cleaning; every report would come out empty.
+    if derived.is_empty() {
+        *processed_data = Some(Vec::new());
+        return Err(if raw_count == 0 {
+            PipelineError::EmptyDataset { reason: "the CSV file(s) have headers but no data rows".to_string() }
+        } else {
+            PipelineError::NoValidRecords { total: raw_count }
+        });
+    }
+
+    // Overlapping exports list the same project more than once; keep its most complete, most recent row.
+    let derived = if csv_files.len() > 1 && settings.remove_duplicates {
+        let file_order: Vec<String> = csv_files.iter().map(|p| source_name(p)).collect();
+        let mut collapsed_count = 0;
+        let reconciled = pipeline.run_stage("reconcile files", derived.len(), || {
+            let (kept, collapsed) = reconcile_cross_file_duplicates(derived, &file_order);
+            collapsed_count = collapsed.len();
+            Ok(kept)
+        })?;
+        if collapsed_count > 0 {
+            warn!("Cross-file duplicates collapsed: {} (kept the most complete, most recent row of each)", collapsed_count);
+        }
+        reconciled
+    } else {
+        derived
+    };
+
+    // Drop duplicate projects, keeping the first occurrence of each.
+    let duplicate_pairs = detect_duplicate_records(&derived);
+    let duplicate_count = duplicate_pairs.len();
+    if duplicate_count > 0 {
+        warn!("Duplicate records detected: {}", duplicate_count);
+        for (first, duplicate) in &duplicate_pairs {
+            trace!("  record {} duplicates record {}", duplicate + 1, first + 1);
+        }
+    }
+    let derived = if duplicate_count > 0 && settings.remove_duplicates {
+        pipeline.run_stage("dedupe", derived.len(), || {
+            let (kept, removed) = remove_duplicates(derived);
+            write_duplicates(&removed, settings)?;
+            Ok(kept)
+        })?
+    } else {
+        derived
+    };
+
+    // Report coordinates shared by projects in different provinces.
+    let coordinate_conflicts = detect_coordinate_conflicts(&derived);
+    if !coordinate_conflicts.is_empty() {
+        warn!("Coordinate conflicts detected: {} locations listed under more than one province", coordinate_conflicts.len());
+        write_coordinate_conflicts(&coordinate_conflicts, settings)?;
+    }
+
+    // Flag projects completed before they started.
+    let mut negative_delays = 0;
+    let checked = pipeline.run_stage("check delays", derived.len(), || {
+        let (checked, count) = flag_negative_delays(derived, settings.null_negative_delays);
+        negative_delays = count;
+        Ok(checked)
+    })?;
+    if negative_delays > 0 {
+        warn!(
+            "Negative delays detected: {} records completed before they started ({})",
+            negative_delays,
+            if settings.null_negative_delays { "delay nulled out" } else { "delay kept" }
+        );
+    }
+
+    // Impute missing coordinates and filter records within the target year range (2021–2023).
+    let imputed = pipeline.run_stage("impute", checked.len(), || Ok(impute_coordinates(checked)))?;
+    info!("Imputed coordinates for {} records", imputed.iter().filter(|r| r.coordinates_imputed).count());
+    let (min_year, max_year) = (rules.min_year, rules.max_year);
+    let filtered = pipeline.run_stage("filter", imputed.len(), || {
+        Ok(filter_by_year_range(imputed, min_year, max_year))
+    })?;
+    println!("({} rows loaded, {} filtered for {}-{})\n", raw_count, filtered.len(), min_year, max_year);
+    pipeline.record_counts.push(("filtered", filtered.len()));
+    if filtered.is_empty() {
+        warn_empty_dataset(&PipelineError::EmptyDataset {
+            reason: format!("no valid records fall within {}-{}", min_year, max_year),
+        });
+    }
+    // Flag delays far above the norm for this dataset, not just past a fixed number of days.
+    let severe_delays = pipeline.run_stage("severe delays", filtered.len(), || Ok(detect_severe_delays(&filtered)))?;
+    if !severe_delays.is_empty() {
+        let (mean, std_dev) = delay_mean_and_std_dev(&filtered);
+        warn!(
+            "Severe delays detected: {} records more than {} standard deviations above the mean delay ({:.1} days, std dev {:.1})",
+            severe_delays.len(),
+            SEVERE_DELAY_Z_SCORE,
+            mean,
+            std_dev
+        );
+        for &i in severe_delays.iter().take(10) {
+            let r = &filtered[i];
+            let delay = r.completion_delay_days.unwrap_or_default();
+            warn!(
+                "  {} days (z = {:.1}): {}, {} {}",
+                delay,
+                (delay as f64 - mean) / std_dev,
+                r.contractor,
+                r.province,
+                r.funding_year
+            );
+        }
+        if severe_delays.len() > 10 {
+            warn!("  ... and {} more (counted in summary.json)", severe_delays.len() - 10);
+        }
+    }
+
+    // Cache the result for the next run; failing to do so only costs a full load next time.
+    if let Some((path, key)) = &cache
+        && !filtered.is_empty()
+    {
+        match write_cache(path, *key, &filtered) {
+            Ok(()) => info!("Processed records cached in: {}", path.display()),
+            Err(e) => warn!("could not write the cache {}: {}", path.display(), e),
+        }
+    }
+    *processed_data = Some(filtered);
+
+    // Report where the time went.
+    if settings.timings {
+        pipeline.print_timings("Pipeline timings");
+    }
+    let stats_path = settings.config.output_path()?.join("pipeline_stats.json");
+    if settings.output.dry_run {
+        println!("Dry run: would write pipeline stats to {}", stats_path.display());
+    } else if settings.overwrite.confirm(&stats_path)? {
+        write_json(&stats_path, &pipeline.to_json())?;
+        info!("Pipeline stats written to: {}", stats_path.display());
+    } else {
+        println!("Kept existing {}", stats_path.display());
+    }
+    info!("Load completed in {:.2}s", load_started.elapsed().as_secs_f64());
+    println!();
+    Ok(pipeline)
+}
+
+/// Loads the dataset again, e.g. after the CSV was edited, and replaces the records in memory.
+/// The records loaded before are kept when the new load fails.
+pub fn reload_file(
+    raw_records: &mut Option<Vec<RawRecord>>,
+    processed_data: &mut Option<Vec<ProcessedRecord>>,
+    settings: &Settings,
+) -> Result<Pipeline, PipelineError> {
+    let (mut new_raw, mut new_processed) = (None, None);
+    let stages = load_file(&mut new_raw, &mut new_processed, settings)?;
+    let new_count = new_processed.as_ref().map_or(0, Vec::len);
+    match processed_data.as_ref().map(Vec::len) {
+        Some(old_count) => println!(
+            "Reloaded dataset: {} records before, {} now ({:+})\n",
+            old_count,
+            new_count,
+            new_count as i64 - old_count as i64
+        ),
+        None => println!("Loaded dataset: {} records (nothing was loaded before)\n", new_count),
+    }
+    *raw_records = new_raw;
+    *processed_data = new_processed;
+    Ok(stages)
+}
+
+/// Whether to use the cached records: interactive runs ask (default yes), batch runs always do.
+fn confirm_cached_load(settings: &Settings, count: usize) -> Result<bool, PipelineError> {
+    if !settings.overwrite.interactive {
+        info!("Using {} cached records", count);
+        return Ok(true);
+    }
+    let prompt = format!("Found {} cached records from an earlier load of the same files. Use them? (Y/N) [Y]: ", count);
+    let answer = ask_question(&prompt)?.to_lowercase();
+    Ok(!matches!(answer.as_str(), "n" | "no"))
+}
+
+/// Returns `dir` if reports can be written there. Otherwise interactive runs
+/// ask for another directory until one works; batch runs, or an empty answer,
+/// give an `OutputNotWritable` error.
+fn writable_output_dir(settings: &Settings, mut dir: PathBuf) -> Result<PathBuf, PipelineError> {
+    loop {
+        let source = match check_writable(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) => e,
+        };
+        if !settings.overwrite.interactive {
+            return Err(PipelineError::OutputNotWritable { dir, source });
+        }
+        println!("Cannot write to {}: {}", dir.display(), source);
+        let answer = ask_question("Enter another output directory, or Enter to cancel: ")?;
+        if answer.is_empty() {
+            return Err(PipelineError::OutputNotWritable { dir, source });
+        }
+        dir = PathBuf::from(answer);
+    }
+}
+
+/// Prints a prominent notice that the loaded dataset has nothing to report on.
+pub fn warn_empty_dataset(error: &PipelineError) {
+    warn!("Dataset is empty: {}", error);
+    println!("\n********************************************************");
+    println!("WARNING: {}.", error);
+    println!("Reports will not be generated for this dataset.");
+    println!("********************************************************\n");
+}
+
+/// The loaded records, or the error explaining why there are none to work with.
+pub fn loaded_records(processed_data: &Option<Vec<ProcessedRecord>>) -> Result<&[ProcessedRecord], PipelineError> {
+    match processed_data {
+        None => {
+            warn!("No data loaded");
+            Err(PipelineError::NoDataLoaded)
+        }
+        Some(data) if data.is_empty() => {
+            warn!("Loaded dataset has no valid records");
+            Err(PipelineError::EmptyDataset { reason: "the loaded dataset is empty".to_string() })
+        }
+        Some(data) => Ok(data),
+    }
+}
+
+/// Write removed duplicate records to `duplicates.csv` in the output directory
+pub fn write_duplicates(removed: &[ProcessedRecord], settings: &Settings) -> Result<(), PipelineError> {
+    let headers = ["Region", "Province", "Contractor", "TypeOfWork", "FundingYear", "StartDate", "ApprovedBudgetForContract", "ContractCost"];
+    let rows: Vec<ReportRow> = removed.iter().map(|r| {
+        let mut row = ReportRow::new();
+        row.insert("Region".to_string(), r.region.clone());
+        row.insert("Province".to_string(), r.province.clone());
+        row.insert("Contractor".to_string(), r.contractor.clone());
+        row.insert("TypeOfWork".to_string(), r.type_of_work.clone());
+        row.insert("FundingYear".to_string(), r.funding_year.to_string());
+        row.insert("StartDate".to_string(), r.start_date.map(|d| d.to_string()).unwrap_or_default());
+        row.insert("ApprovedBudgetForContract".to_string(), format_number(r.approved_budget_for_contract, 2));
+        row.insert("ContractCost".to_string(), format_number(r.contract_cost, 2));
+        row
+    }).collect();
+    let file_path = settings.config.output_path()?.join("duplicates.csv");
+    if settings.output.dry_run {
+        println!("Dry run: would write {} rows to {}", rows.len(), file_path.display());
+        return Ok(());
+    }
+    if !settings.overwrite.confirm(&file_path)? {
+        println!("Kept existing {}", file_path.display());
+        return Ok(());
+    }
+    write_csv(&file_path, &rows, &headers, Delimiter::Comma, settings.output.excel_compat)?;
+    info!("Removed duplicates written to: {}", file_path.display());
+    Ok(())
+}
+
+/// Write coordinates shared by several provinces to `coordinate_conflicts.csv` in the output directory
+pub fn write_coordinate_conflicts(conflicts: &[CoordinateConflict], settings: &Settings) -> Result<(), PipelineError> {
+    let headers = ["Latitude", "Longitude", "NumProvinces", "NumRecords", "Provinces"];
+    let rows: Vec<ReportRow> = conflicts.iter().map(|c| {
+        let provinces: Vec<String> = c.provinces.iter().map(|(name, count)| format!("{} ({})", name, count)).collect();
+        let mut row = ReportRow::new();
+        row.insert("Latitude".to_string(), c.latitude.to_string());
+        row.insert("Longitude".to_string(), c.longitude.to_string());
+        row.insert("NumProvinces".to_string(), c.provinces.len().to_string());
+        row.insert("NumRecords".to_string(), c.provinces.iter().map(|(_, count)| count).sum::<usize>().to_string());
+        row.insert("Provinces".to_string(), provinces.join("; "));
+        row
+    }).collect();
+    let file_path = settings.config.output_path()?.join("coordinate_conflicts.csv");
+    if settings.output.dry_run {
+        println!("Dry run: would write {} rows to {}", rows.len(), file_path.display());
+        return Ok(());
+    }
+    if !settings.overwrite.confirm(&file_path)? {
+        println!("Kept existing {}", file_path.display());
+        return Ok(());
+    }
+    write_csv(&file_path, &rows, &headers, Delimiter::Comma, settings.output.excel_compat)?;
+    info!("Coordinate conflicts written to: {}", file_path.display());
+    Ok(())
+}
+
+/// Write records whose MainIsland does not match their Region to `island_warnings.csv` in the output directory
+pub fn write_island_warnings(warnings: &[IslandWarning], settings: &Settings) -> Result<(), PipelineError> {
+    let headers = ["SourceFile", "Row", "Region", "MainIsland", "ExpectedIsland"];
+    let rows: Vec<ReportRow> = warnings.iter().map(|w| {
+        let mut row = ReportRow::new();
+        row.insert("SourceFile".to_string(), w.source_file.clone());
+        row.insert("Row".to_string(), w.row.to_string());
+        row.insert("Region".to_string(), w.region.clone());
+        row.insert("MainIsland".to_string(), w.main_island.clone());
+        row.insert("ExpectedIsland".to_string(), w.expected_island.clone());
+        row
+    }).collect();
+    let file_path = settings.config.output_path()?.join("island_warnings.csv");
+    if settings.output.dry_run {
+        println!("Dry run: would write {} rows to {}", rows.len(), file_path.display());
+        return Ok(());
+    }
+    if !settings.overwrite.confirm(&file_path)? {
+        println!("Kept existing {}", file_path.display());
+        return Ok(());
+    }
+    write_csv(&file_path, &rows, &headers, Delimiter::Comma, settings.output.excel_compat)?;
+    info!("Island mismatches written to: {}", file_path.display());
+    Ok(())
+}
+
+/// Adds a `_<year>` suffix before the extension of an output filename,
+/// e.g. `summary.json` becomes `summary_2022.json`. Without a year the name is unchanged.
+pub fn year_suffixed(filename: &str, year: Option<i32>) -> String {
+    let Some(year) = year else {
+        return filename.to_string();
+    };
+    let path = Path::new(filename);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let renamed = match path.extension() {
+        Some(ext) => format!("{}_{}.{}", stem, year, ext.to_string_lossy()),
+        None => format!("{}_{}", stem, year),
+    };
+    path.with_file_name(renamed).to_string_lossy().to_string()
+}
+
+/// Files written for one entry of a report selection ("1".."9", or "s" for the summary)
+/// in the given output format.
+pub fn report_outputs(config: &Config, output_dir: &Path, report: &str, year: Option<i32>, format: OutputFormat) -> Vec<PathBuf> {
+    let csv_file = match report {
+        "1" => &config.report1_file,
+        "2" => &config.report2_file,
+        "3" => &config.report3_file,
+        "6" => &config.report6_file,
+        "8" => &config.report8_file,
+        "9" => &config.report9_file,
+        _ => return vec![output_dir.join(year_suffixed(&config.summary_file, year))],
+    };
+    let csv_path = output_dir.join(year_suffixed(csv_file, year));
+    let tsv_path = csv_path.with_extension("tsv");
+    let html_file = year_suffixed(&format!("report{}.html", report), year);
+    let json_path = csv_path.with_extension("json");
+    let mut outputs = Vec::new();
+    if format.writes_csv() {
+        outputs.extend([csv_path, tsv_path]);
+    }
+    if format.writes_json() {
+        outputs.push(json_path);
+    }
+    outputs.push(output_dir.join(html_file));
+    outputs
+}
+
+/// Report ids ("1".."9", "s") switched on in a selection, in generation order.
+pub fn selected_reports(selection: &ReportSelection) -> Vec<&'static str> {
+    [
+        (selection.report1, "1"),
+        (selection.report2, "2"),
+        (selection.report3, "3"),
+        (selection.report6, "6"),
+        (selection.report8, "8"),
+        (selection.report9, "9"),
+        (selection.summary, "s"),
+    ]
+    .into_iter()
+    .filter_map(|(on, id)| on.then_some(id))
+    .collect()
+}
+
+/// Lists the output files a batch run would replace, so the run can refuse
+/// before touching anything. Versioned runs only share the load-time files.
+pub fn batch_output_conflicts(settings: &Settings, selection: &ReportSelection) -> io::Result<Vec<PathBuf>> {
+    let output_dir = settings.config.output_path()?;
+    let mut planned = vec![
+        output_dir.join("pipeline_stats.json"),
+        output_dir.join("duplicates.csv"),
+        output_dir.join("coordinate_conflicts.csv"),
+        output_dir.join("island_warnings.csv"),
+    ];
+    if settings.export_cleaned {
+        planned.push(output_dir.join("cleaned_records.json"));
+        planned.push(output_dir.join("cleaned_records_imputed.json"));
+    }
+    if !settings.versioned_output {
+        for report in selected_reports(selection) {
+            planned.extend(report_outputs(&settings.config, &output_dir, report, settings.report_year, settings.output.format));
+        }
+    }
+    Ok(planned.into_iter().filter(|path| path.exists()).collect())
+}
+
+/// Generate all reports
+/// With a `year`, only that funding year is reported on and every output filename gets a `_<year>` suffix.
+/// With `--timings` each report is timed, and the summary also records these timings
+/// and `load_stages`, the stages of the load that produced the data.
+/// A report named in `sorts` has its rows reordered before they are written.
+pub fn generate_reports(
+    processed_data: &Option<Vec<ProcessedRecord>>,
+    settings: &Settings,
+    selection: &ReportSelection,
+    filters: &[RecordFilter],
+    sorts: &[ReportSort],
+    year: Option<i32>,
+    load_stages: Option<&Pipeline>,
+) -> Result<(), PipelineError> {
+    // Ensure data is loaded before generating reports.
+    let data = loaded_records(processed_data)?;
+
+    // Narrow a copy of the data with the active filters, keeping the full set in memory.
+    let filtered;
+    let data: &[ProcessedRecord] = if filters.is_empty() {
+        data
+    } else {
+        filtered = apply_filters(data, filters);
+        info!("Filtered {} → {} records", data.len(), filtered.len());
+        if filtered.is_empty() {
+            return Err(PipelineError::EmptyDataset { reason: "the filter matched no records".to_string() });
+        }
+        &filtered
+    };
+
+    // Narrow further to a single funding year when one was chosen.
+    let single_year;
+    let data: &[ProcessedRecord] = match year {
+        Some(year) => {
+            single_year = filter_by_year_range(data.to_vec(), year, year);
+            info!("Year {}: {} → {} records", year, data.len(), single_year.len());
+            if single_year.is_empty() {
+                return Err(PipelineError::EmptyDataset { reason: format!("no records were funded in {}", year) });
+            }
+            &single_year
+        }
+        None => data,
+    };
+    // Time each report; the table is printed at the end with `--timings`.
+    let mut timings = Pipeline::new();
+    timings.record_counts.push(("reported", data.len()));
+    let (start_year, end_year) = year.map_or((settings.validation.min_year, settings.validation.max_year), |y| (y, y));
+    let file_name = |name: &str| year_suffixed(name, year);
+
+    // Headings and spacing are left out in quiet mode, where each report prints one line.
+    let quiet = settings.preview.quiet;
+    let announce = |text: &str| {
+        if !quiet {
+            println!("{}", text);
+        }
+    };
+
+    announce("Generating reports...\n");
+    let dry_run = settings.output.dry_run;
+    // Check the output directory before writing anything, so a read-only one cannot leave a partial set.
+    let relocated;
+    let default_dir = settings.config.output_path()?;
+    let chosen_dir = if dry_run { default_dir.clone() } else { writable_output_dir(settings, default_dir.clone())? };
+    let config = if chosen_dir == default_dir {
+        &settings.config
+    } else {
+        relocated = Config { output_dir: chosen_dir.display().to_string(), ..settings.config.clone() };
+        &relocated
+    };
+    // Versioned runs share one timestamped directory for every file of this run.
+    let output_dir = if settings.versioned_output {
+        let run_dir = config.create_run_dir(dry_run)?;
+        println!("Writing this run to {}", run_dir.display());
+        run_dir
+    } else {
+        config.output_path()?
+    };
+
+    // Every file this run writes, for the manifest; files from earlier runs are left out of it.
+    let mut written: Vec<PathBuf> = Vec::new();
+    let mut record_outputs = |report: &str, rows: usize| {
+        if rows > 0 || settings.output.write_empty {
+            written.extend(report_outputs(config, &output_dir, report, year, settings.output.format));
+        }
+    };
+
+    // Ask before replacing any of a report's files; a refusal skips that report.
+    let may_write = |report: &str| -> Result<bool, PipelineError> {
+        if dry_run {
+            return Ok(true);
+        }
+        let allowed = settings.overwrite.confirm_all(&report_outputs(config, &output_dir, report, year, settings.output.format))?;
+        if !allowed {
+            println!("Skipped report {}; existing files were kept.\n", report.to_uppercase());
+        }
+        Ok(allowed)
+    };
+
+    // Report 1
+    if selection.report1 && may_write("1")? {
+        announce("Report 1: Regional Flood Mitigation Efficiency Summary");
+        let started = Instant::now();
+        let mut r1 = generate_report1(data, settings.budget_decimals);
+        apply_report_sort(&mut r1, sorts, "1");
+        let r1_total = build_total_row(&r1, "Region", format!("TOTAL ({} regions)", r1.len()), &["TotalBudget"]);
+        write_report(
+            &output_dir,
+            &file_name(&config.report1_file),
+            &r1,
+            "Report 1: Regional Flood Mitigation Efficiency Summary",
+            &settings.preview,
+            &settings.output,
+            &r1_total,
+        )?;
+        write_html_page(&output_dir, &file_name("report1.html"), &r1, &r1_total, "Report 1: Regional Flood Mitigation Efficiency Summary", &settings.output)?;
+        if !quiet {
+            print_bar_chart("Efficiency Score by Region", &r1, "Region", "EfficiencyScore");
+        }
+        record_outputs("1", r1.len());
+        timings.record("report 1", data.len(), r1.len(), started);
+        announce("");
+    }
+
+    // Report 2
+    if selection.report2 && may_write("2")? {
+        announce("Report 2: Top Contractors Performance Ranking");
+        let started = Instant::now();
+        let mut r2 = generate_report2(data, settings.top_n, settings.include_unknown_contractors, settings.budget_decimals);
+        apply_report_sort(&mut r2, sorts, "2");
+        let r2_total = build_total_row(&r2, "Rank", "TOTAL".to_string(), &["TotalCost", "NumProjects", "TotalSavings"]);
+        write_report(
+            &output_dir,
+            &file_name(&config.report2_file),
+            &r2,
+            "Report 2: Top Contractors Performance Ranking",
+            &settings.preview,
+            &settings.output,
+            &r2_total,
+        )?;
+        write_html_page(&output_dir, &file_name("report2.html"), &r2, &r2_total, "Report 2: Top Contractors Performance Ranking", &settings.output)?;
+        if !quiet {
+            print_bar_chart("Reliability Index by Contractor", &r2, "Contractor", "ReliabilityIndex");
+        }
+        let unknown = data.iter().filter(|r| r.contractor == "Unknown").count();
+        if unknown > 0 && !settings.include_unknown_contractors {
+            println!("Note: {} projects with an unknown contractor were excluded from the ranking.", unknown);
+        }
+        record_outputs("2", r2.len());
+        timings.record("report 2", data.len(), r2.len(), started);
+        announce("");
+    }
+
+    // Report 3
+    if selection.report3 && may_write("3")? {
+        announce("Report 3: Annual Project Type Cost Overrun Trends");
+        let started = Instant::now();
+        let mut r3 = generate_report3(data, settings.moving_average_years);
+        apply_report_sort(&mut r3, sorts, "3");
+        let r3_total = build_total_row(&r3, "FundingYear", "TOTAL".to_string(), &["TotalProjects"]);
+        write_report(
+            &output_dir,
+            &file_name(&config.report3_file),
+            &r3,
+            "Report 3: Annual Project Type Cost Overrun Trends",
+            &settings.preview,
+            &settings.output,
+            &r3_total,
+        )?;
+        write_html_page(&output_dir, &file_name("report3.html"), &r3, &r3_total, "Report 3: Annual Project Type Cost Overrun Trends", &settings.output)?;
+        record_outputs("3", r3.len());
+        timings.record("report 3", data.len(), r3.len(), started);
+        announce("");
+    }
+
+    // Report 6
+    if selection.report6 && may_write("6")? {
+        announce("Report 6: Savings by Main Island");
+        let started = Instant::now();
+        let mut r6 = generate_report6(data, settings.budget_decimals);
+        apply_report_sort(&mut r6, sorts, "6");
+        let r6_total = build_total_row(&r6, "MainIsland", "TOTAL".to_string(), &["TotalBudget", "TotalContractCost", "TotalSavings", "NumProjects"]);
+        write_report(
+            &output_dir,
+            &file_name(&config.report6_file),
+            &r6,
+            "Report 6: Savings by Main Island",
+            &settings.preview,
+            &settings.output,
+            &r6_total,
+        )?;
+        write_html_page(&output_dir, &file_name("report6.html"), &r6, &r6_total, "Report 6: Savings by Main Island", &settings.output)?;
+        record_outputs("6", r6.len());
+        timings.record("report 6", data.len(), r6.len(), started);
+        announce("");
+    }
+
+    // Report 8
+    if selection.report8 && may_write("8")? {
+        announce("Report 8: Island Group Budget vs Contract Cost Waterfall");
+        let started = Instant::now();
+        let mut r8 = generate_report8(data, settings.budget_decimals);
+        apply_report_sort(&mut r8, sorts, "8");
+        let r8_total = build_total_row(&r8, "FundingYear", "TOTAL".to_string(), &["TotalApprovedBudget", "TotalContractCost", "TotalSavings", "NumProjects"]);
+        write_report(
+            &output_dir,
+            &file_name(&config.report8_file),
+            &r8,
+            "Report 8: Island Group Budget vs Contract Cost Waterfall",
+            &settings.preview,
+            &settings.output,
+            &r8_total,
+        )?;
+        write_html_page(&output_dir, &file_name("report8.html"), &r8, &r8_total, "Report 8: Island Group Budget vs Contract Cost Waterfall", &settings.output)?;
+        record_outputs("8", r8.len());
+        timings.record("report 8", data.len(), r8.len(), started);
+        announce("");
+    }
+
+    // Report 9
+    if selection.report9 && may_write("9")? {
+        announce("Report 9: Quarterly Trends by Start Date");
+        let started = Instant::now();
+        let mut r9 = generate_report9(data, settings.budget_decimals);
+        apply_report_sort(&mut r9, sorts, "9");
+        let r9_total = build_total_row(&r9, "FundingYear", "TOTAL".to_string(), &["NumProjects", "TotalBudget"]);
+        write_report(
+            &output_dir,
+            &file_name(&config.report9_file),
+            &r9,
+            "Report 9: Quarterly Trends by Start Date",
+            &settings.preview,
+            &settings.output,
+            &r9_total,
+        )?;
+        write_html_page(&output_dir, &file_name("report9.html"), &r9, &r9_total, "Report 9: Quarterly Trends by Start Date", &settings.output)?;
+        record_outputs("9", r9.len());
+        timings.record("report 9", data.len(), r9.len(), started);
+        announce("");
+    }
+
+    // Summary
+    if selection.summary && may_write("s")? {
+        announce("Generating summary...");
+        let started = Instant::now();
+        let summary_file = file_name(&config.summary_file);
+        let mut summary = generate_summary(data, start_year, end_year);
+        if settings.timings {
+            summary["timings"] = json!({
+                "load": load_stages.map(Pipeline::to_json),
+                "reports": timings.to_json(),
+            });
+        }
+        written.push(write_summary(&summary, &output_dir, &summary_file, dry_run)?);
+
+        // Print final summary report in readable JSON format (always shown on a dry run,
+        // since nothing is written to disk).
+        if quiet && !dry_run {
+            println!("Summary: {}", output_dir.join(&summary_file).display());
+        } else {
+            println!("\nOutputs saved to individual files...\n");
+            println!("Summary Stats ({}):", summary_file);
+            println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+        }
+        timings.record("summary", data.len(), 1, started);
+    } else {
+        announce("Outputs saved to individual files...\n");
+    }
+
+    // List the files written above with their checksums, for `--verify-manifest`.
+    if !dry_run {
+        let started = Instant::now();
+        let manifest_path = write_manifest(&output_dir, &written)?;
+        info!("Manifest written to: {}", manifest_path.display());
+        timings.record("manifest", 1, 1, started);
+    }
+
+    if settings.timings {
+        timings.print_timings("Report timings");
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Re-hashes the files listed in `dir`'s manifest and prints the ones that changed.
+/// Any missing or changed file is a `ManifestMismatch` error.
+pub fn verify_output_manifest(dir: &Path) -> Result<(), PipelineError> {
+    let manifest = read_manifest(dir)?;
+    let problems = verify_manifest(dir, &manifest)?;
+    if problems.is_empty() {
+        println!("All {} files in {} match the manifest.", manifest.files.len(), dir.display());
+        return Ok(());
+    }
+    for (file, problem) in &problems {
+        println!("  {}: {}", file, problem);
+    }
+    Err(PipelineError::ManifestMismatch { count: problems.len() })
+}
+
+/// Converts a record into an export row with raw numbers, ISO dates, and blanks for missing values.
+pub fn processed_record_row(r: &ProcessedRecord) -> ReportRow {
+    let opt = |value: Option<String>| value.unwrap_or_default();
+    let mut row = ReportRow::new();
+    row.insert("Region".to_string(), r.region.clone());
+    row.insert("MainIsland".to_string(), r.main_island.clone());
+    row.insert("FundingYear".to_string(), r.funding_year.to_string());
+    row.insert("ApprovedBudgetForContract".to_string(), r.approved_budget_for_contract.to_string());
+    row.insert("ContractCost".to_string(), r.contract_cost.to_string());
+    row.insert("StartDate".to_string(), opt(r.start_date.map(|d| d.to_string())));
+    row.insert("ActualCompletionDate".to_string(), opt(r.actual_completion_date.map(|d| d.to_string())));
+    row.insert("ProjectLatitude".to_string(), opt(r.project_latitude.map(|v| v.to_string())));
+    row.insert("ProjectLongitude".to_string(), opt(r.project_longitude.map(|v| v.to_string())));
+    row.insert("Province".to_string(), r.province.clone());
+    row.insert("Contractor".to_string(), r.contractor.clone());
+    row.insert("TypeOfWork".to_string(), r.type_of_work.clone());
+    row.insert("CostSavings".to_string(), r.cost_savings.to_string());
+    row.insert("CompletionDelayDays".to_string(), opt(r.completion_delay_days.map(|d| d.to_string())));
+    row.insert("CoordinatesImputed".to_string(), r.coordinates_imputed.to_string());
+    row
+}
+
+/// Export the records used for reports (after the active filters) to `processed_data.csv`
+pub fn export_processed_data(
+    processed_data: &Option<Vec<ProcessedRecord>>,
+    settings: &Settings,
+    filters: &[RecordFilter],
+) -> Result<(), PipelineError> {
+    let data = loaded_records(processed_data)?;
+
+    let records = apply_filters(data, filters);
+    if !filters.is_empty() {
+        let active: Vec<String> = filters.iter().map(|f| f.to_string()).collect();
+        println!("Active filters: {}", active.join("; "));
+    }
+
+    let rows: Vec<ReportRow> = records.iter().map(processed_record_row).collect();
+    let file_path = settings.config.output_path()?.join("processed_data.csv");
+    if !settings.overwrite.confirm(&file_path)? {
+        println!("Export cancelled; {} was kept.\n", file_path.display());
+        return Ok(());
+    }
+    write_csv(&file_path, &rows, &PROCESSED_HEADERS, Delimiter::Comma, settings.output.excel_compat)?;
+    println!("Exported {} of {} records to: {}", rows.len(), data.len(), file_path.display());
+
+    let json_path = file_path.with_extension("json");
+    if !settings.overwrite.confirm(&json_path)? {
+        println!("JSON export skipped; {} was kept.\n", json_path.display());
+        return Ok(());
+    }
+    write_processed_json(&json_path, &records)?;
+    println!("Exported the same records as JSON to: {}\n", json_path.display());
+    Ok(())
+}
+
+/// Export the loaded records as cleaned (`cleaned_records.json`) and with missing fields
+/// filled from the dataset medians (`cleaned_records_imputed.json`).
+/// Works from the records in memory, so streamed and cached loads export the same thing.
+pub fn export_cleaned_records(processed_data: &Option<Vec<ProcessedRecord>>, settings: &Settings) -> Result<(), PipelineError> {
+    let data = loaded_records(processed_data)?;
+
+    let cleaned: Vec<CleanedRecord> = data.iter().map(strip_derived_fields).collect();
+    let medians = compute_dataset_medians(&cleaned);
+    info!(
+        "Dataset medians from {} complete records: latitude {:?}, longitude {:?}, delay {:?} days",
+        medians.complete_records, medians.latitude, medians.longitude, medians.delay_days
+    );
+    let output_dir = settings.config.output_path()?;
+    let strict_path = output_dir.join("cleaned_records.json");
+    let imputed_path = output_dir.join("cleaned_records_imputed.json");
+    if settings.output.dry_run {
+        println!("Dry run: would write {} and {}\n", strict_path.display(), imputed_path.display());
+        return Ok(());
+    }
+    if !settings.overwrite.confirm(&strict_path)? {
+        println!("Export cancelled; {} was kept.\n", strict_path.display());
+        return Ok(());
+    }
+    write_cleaned_json(&strict_path, &cleaned)?;
+    println!("Exported {} cleaned records to: {}", cleaned.len(), strict_path.display());
+
+    if !settings.overwrite.confirm(&imputed_path)? {
+        println!("Imputed export skipped; {} was kept.\n", imputed_path.display());
+        return Ok(());
+    }
+    write_cleaned_json(&imputed_path, &fill_missing_with_medians(cleaned, &medians))?;
+    println!("Exported the median-imputed copy to: {}\n", imputed_path.display());
+    Ok(())
+}
+
+/// Export processed records to a SQLite database in the output directory
+pub fn export_sqlite(processed_data: &Option<Vec<ProcessedRecord>>, settings: &Settings) -> Result<(), PipelineError> {
+    let data = loaded_records(processed_data)?;
+
+    let db_path = settings.config.output_path()?.join("flood_control_projects.db");
+    if !settings.overwrite.confirm(&db_path)? {
+        println!("Export cancelled; {} was kept.\n", db_path.display());
+        return Ok(());
+    }
+    match export_to_sqlite(data, &db_path) {
+        Ok(rows) => println!("Exported {} records to: {}\n", rows, db_path.display()),
+        Err(e) => {
+            warn!("SQLite export to {} failed: {}", db_path.display(), e);
+            println!("Error: SQLite export failed: {}\n", e);
+        }
+    }
+    Ok(())
+}
+
+/// Converts a record into a row for the contractor search table and export.
+pub fn search_result_row(record: &ProcessedRecord) -> ReportRow {
+    let mut row = ReportRow::new();
+    row.insert("Contractor".to_string(), record.contractor.clone());
+    row.insert("Region".to_string(), record.region.clone());
+    row.insert("Province".to_string(), record.province.clone());
+    row.insert("FundingYear".to_string(), record.funding_year.to_string());
+    row.insert("ApprovedBudget".to_string(), format_number(record.approved_budget_for_contract, 2));
+    row.insert("ContractCost".to_string(), format_number(record.contract_cost, 2));
+    row.insert("CostSavings".to_string(), format_number(record.cost_savings, 2));
+    row.insert("DelayDays".to_string(), record.completion_delay_days.map(|d| d.to_string()).unwrap_or_default());
+    row
+}
+
+/// Search projects by contractor keyword, page through the matches, and optionally export them
+pub fn search_contractor(processed_data: &Option<Vec<ProcessedRecord>>, settings: &Settings) -> Result<(), PipelineError> {
+    let data = loaded_records(processed_data)?;
+
+    let keyword = ask_question("Enter contractor keyword: ")?;
+    if keyword.is_empty() {
+        println!("No keyword entered.\n");
+        return Ok(());
+    }
+
+    let matches = search_contractor_records(data, &keyword);
+    if matches.is_empty() {
+        println!("No projects found for contractors matching '{}'.\n", keyword);
+        return Ok(());
+    }
+    println!("Found {} projects for contractors matching '{}'.\n", matches.len(), keyword);
+
+    let rows: Vec<ReportRow> = matches.iter().map(search_result_row).collect();
+
+    // Print one page at a time until the user stops or the matches run out.
+    let header_style = if settings.preview.use_color { "bFg" } else { "b" };
+    let mut shown = 0;
+    while shown < rows.len() {
+        let mut table = Table::new();
+        table.set_format(console_table_format());
+        table.add_row(Row::new(SEARCH_HEADERS.iter().map(|&h| Cell::new(h).style_spec(header_style)).collect()));
+        for row in rows.iter().skip(shown).take(SEARCH_PAGE_SIZE) {
+            table.add_row(Row::new(SEARCH_HEADERS.iter().map(|&h| Cell::new(&row[h])).collect()));
+        }
+        table.printstd();
+
+        shown = (shown + SEARCH_PAGE_SIZE).min(rows.len());
+        println!("Showing {} of {} matches", shown, rows.len());
+        if shown < rows.len() {
+            let more = ask_question("Show more (Y/N): ")?;
+            if more.to_uppercase() != "Y" {
+                break;
+            }
+        }
+    }
+
+    let export = ask_question("Export matches to CSV (Y/N): ")?;
+    if export.to_uppercase() == "Y" {
+        let file_path = settings.config.output_path()?.join(format!("search_{}.csv", filename_safe(&keyword)));
+        if settings.overwrite.confirm(&file_path)? {
+            write_csv(&file_path, &rows, &SEARCH_HEADERS, Delimiter::Comma, settings.output.excel_compat)?;
+            println!("Matches written to: {}", file_path.display());
+        } else {
+            println!("Export cancelled; {} was kept.", file_path.display());
+        }
+    }
+    println!();
+    Ok(())
+}
+
+/// Lowercases `text` and replaces anything but ASCII letters and digits with `_`,
+/// so it can be part of a file name.
+pub fn filename_safe(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+/// Asks for a contractor and lists every one of their projects with the contractor's
+/// reliability index, writing the list to `contractor_<name>.csv`.
+pub fn contractor_drilldown(processed_data: &Option<Vec<ProcessedRecord>>, settings: &Settings) -> Result<(), PipelineError> {
+    let data = loaded_records(processed_data)?;
+
+    let name = ask_question("Enter contractor name: ")?;
+    if name.is_empty() {
+        println!("No name entered.\n");
+        return Ok(());
+    }
+
+    // A partial name can match several contractors; the user picks one of the first few.
+    let candidates = find_contractors(data, &name);
+    let contractor = match candidates.as_slice() {
+        [] => {
+            println!("No contractor matches '{}'.\n", name);
+            return Ok(());
+        }
+        [one] => one.clone(),
+        several => {
+            println!("{} contractors match '{}':", several.len(), name);
+            for (i, candidate) in several.iter().take(DRILLDOWN_CANDIDATES).enumerate() {
+                println!("  [{}] {}", i + 1, candidate);
+            }
+            if several.len() > DRILLDOWN_CANDIDATES {
+                println!("  ... and {} more (type more of the name to narrow the list)", several.len() - DRILLDOWN_CANDIDATES);
+            }
+            let answer = ask_question("Pick a number, or Enter to cancel: ")?;
+            match answer.parse::<usize>().ok().and_then(|n| several.iter().take(DRILLDOWN_CANDIDATES).nth(n.wrapping_sub(1))) {
+                Some(picked) => picked.clone(),
+                None => {
+                    println!("No contractor picked.\n");
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    let (rows, reliability_index) = generate_contractor_drilldown(data, &contractor);
+    println!(
+        "{}: {} projects, reliability index {} ({})",
+        contractor,
+        rows.len(),
+        format_number(reliability_index, 2),
+        risk_flag(reliability_index)
+    );
+
+    let output_dir = settings.config.output_path()?;
+    let file_name = format!("contractor_{}.csv", filename_safe(&contractor));
+    let file_path = output_dir.join(&file_name);
+    if !settings.output.dry_run && !settings.overwrite.confirm(&file_path)? {
+        println!("Kept existing {}\n", file_path.display());
+        return Ok(());
+    }
+    let total = build_total_row(
+        &rows,
+        "FundingYear",
+        format!("TOTAL (reliability {})", format_number(reliability_index, 2)),
+        &["ContractCost", "CostSavings"],
+    );
+    write_report(
+        &output_dir,
+        &file_name,
+        &rows,
+        &format!("Projects of {}", contractor),
+        &settings.preview,
+        &settings.output,
+        &total,
+    )?;
+    Ok(())
+}
+
+/// Lists the fields of one record as (name, value) pairs for the detail viewer.
+/// Dates are shown in ISO format and missing optional values as "N/A".
+pub fn record_detail_fields(record: &ProcessedRecord) -> Vec<(&'static str, String)> {
+    let or_na = |value: Option<String>| value.unwrap_or_else(|| "N/A".to_string());
+    vec![
+        ("Region", record.region.clone()),
+        ("MainIsland", record.main_island.clone()),
+        ("Province", record.province.clone()),
+        ("FundingYear", record.funding_year.to_string()),
+        ("Contractor", record.contractor.clone()),
+        ("TypeOfWork", record.type_of_work.clone()),
+        ("ApprovedBudgetForContract", format_number(record.approved_budget_for_contract, 2)),
+        ("ContractCost", format_number(record.contract_cost, 2)),
+        ("StartDate", or_na(record.start_date.map(|d| d.to_string()))),
+        ("ActualCompletionDate", or_na(record.actual_completion_date.map(|d| d.to_string()))),
+        ("ProjectLatitude", or_na(record.project_latitude.map(|v| format_number(v, 6)))),
+        ("ProjectLongitude", or_na(record.project_longitude.map(|v| format_number(v, 6)))),
+        ("CoordinatesImputed", if record.coordinates_imputed { "Yes" } else { "No" }.to_string()),
+        ("CostSavings", format_number(record.cost_savings, 2)),
+        ("CompletionDelayDays", or_na(record.completion_delay_days.map(|d| d.to_string()))),
+    ]
+}
+
+/// Browse processed records one at a time, optionally narrowed by filters first
+pub fn view_records(processed_data: &Option<Vec<ProcessedRecord>>) -> Result<(), PipelineError> {
+    let data = loaded_records(processed_data)?;
+
+    let answer = ask_question("Filter records first (e.g. province=Cebu; year=2022), or Enter for all: ")?;
+    let records = if answer.is_empty() {
+        data.to_vec()
+    } else {
+        match parse_filter_list(&answer) {
+            Some(filters) => apply_filters(data, &filters),
+            None => {
+                println!("Invalid filter '{}'. Use field=value pairs separated by ';'.\n", answer);
+                return Ok(());
+            }
+        }
+    };
+    if records.is_empty() {
+        println!("No records match the filter.\n");
+        return Ok(());
+    }
+
+    let mut index = 0;
+    loop {
+        println!("\nRecord {} of {}", index + 1, records.len());
+        let mut table = Table::new();
+        table.set_format(console_table_format());
+        for (field, value) in record_detail_fields(&records[index]) {
+            table.add_row(Row::new(vec![Cell::new(field).style_spec("b"), Cell::new(&value)]));
+        }
+        table.printstd();
+
+        let command = ask_question("[n]ext, [p]revious, row number, or [q]uit: ")?;
+        match command.to_lowercase().as_str() {
+            "n" | "" => {
+                if index + 1 < records.len() {
+                    index += 1;
+                } else {
+                    println!("Already at the last record.");
+                }
+            }
+            "p" => {
+                if index > 0 {
+                    index -= 1;
+                } else {
+                    println!("Already at the first record.");
+                }
+            }
+            "q" => break,
+            other => match other.parse::<usize>() {
+                Ok(row) if (1..=records.len()).contains(&row) => index = row - 1,
+                _ => println!("Enter n, p, q, or a row number between 1 and {}.", records.len()),
+            },
+        }
+    }
+    println!();
+    Ok(())
+}
+
+/// Re-render a generated report from its CSV in pages, with wide cells truncated
+pub fn view_full_report(settings: &Settings) -> Result<(), PipelineError> {
+    let config = &settings.config;
+    println!("[1] Regional Flood Mitigation Efficiency Summary");
+    println!("[2] Top Contractors Performance Ranking");
+    println!("[3] Annual Project Type Cost Overrun Trends");
+    println!("[6] Savings by Main Island");
+    println!("[8] Island Group Budget vs Contract Cost Waterfall");
+    println!("[9] Quarterly Trends by Start Date");
+    let filename = match ask_question("Report to view: ")?.as_str() {
+        "1" => &config.report1_file,
+        "2" => &config.report2_file,
+        "3" => &config.report3_file,
+        "6" => &config.report6_file,
+        "8" => &config.report8_file,
+        "9" => &config.report9_file,
+        other => {
+            println!("Invalid report '{}'.\n", other);
+            return Ok(());
+        }
+    };
+
+    let report_dir = match config.latest_run_path()? {
+        Some(run_dir) if settings.versioned_output => run_dir,
+        _ => config.output_path()?,
+    };
+    let file_path = report_dir.join(filename);
+    if !file_path.exists() {
+        println!("{} has not been generated yet. Generate it first (option 2).\n", file_path.display());
+        return Ok(());
+    }
+
+    let mut rdr = ReaderBuilder::new().from_path(&file_path)?;
+    let headers: Vec<String> = rdr.headers()?.iter().map(|h| h.to_string()).collect();
+    let mut rows = Vec::new();
+    for record in rdr.records() {
+        rows.push(record?);
+    }
+    // The grand-total row written by `write_report` is shown on every page instead of counted.
+    let total = rows.pop_if(|row| row.iter().any(|v| v.starts_with("TOTAL")));
+
+    let header_style = if settings.preview.use_color { "bFg" } else { "b" };
+    let mut shown = 0;
+    while shown < rows.len() {
+        let mut table = Table::new();
+        table.set_format(console_table_format());
+        table.add_row(Row::new(headers.iter().map(|h| Cell::new(h).style_spec(header_style)).collect()));
+        for row in rows.iter().skip(shown).take(REPORT_PAGE_SIZE) {
+            table.add_row(Row::new(row.iter().map(|v| Cell::new(&truncate_cell(v, REPORT_CELL_WIDTH))).collect()));
+        }
+        if let Some(total) = &total {
+            table.add_row(Row::new(total.iter().map(|v| Cell::new(&truncate_cell(v, REPORT_CELL_WIDTH)).style_spec("b")).collect()));
+        }
+        table.printstd();
+
+        let end = (shown + REPORT_PAGE_SIZE).min(rows.len());
+        println!("Rows {}-{} of {}", shown + 1, end, rows.len());
+        shown = end;
+        if shown < rows.len() {
+            let answer = ask_question(&format!("... ({} more rows) Enter for next page, q to quit: ", rows.len() - shown))?;
+            if answer.eq_ignore_ascii_case("q") {
+                break;
+            }
+        }
+    }
+    println!();
+    Ok(())
+}
+
+/// Ask for optional filters, a single year, and which reports to generate, then generate them.
+/// The chosen filters and year are remembered for the next run; the loaded data is never modified.
+pub fn run_report_menu(
+    processed_data: &Option<Vec<ProcessedRecord>>,
+    settings: &Settings,
+    filters: &mut Vec<RecordFilter>,
+    sorts: &mut Vec<ReportSort>,
+    year: &mut Option<i32>,
+    load_stages: Option<&Pipeline>,
+) -> Result<(), PipelineError> {
+    // Skip the prompts when there is nothing to report on; the generator returns the error.
+    if processed_data.as_ref().is_none_or(|d| d.is_empty()) {
+        return generate_reports(processed_data, settings, &ReportSelection::all(), filters, sorts, *year, load_stages);
+    }
+
+    let current: Vec<String> = filters.iter().map(|f| f.to_string()).collect();
+    println!("Filter records by region, province, island, year, contractor, or type.");
+    println!("Current filters: {}", if current.is_empty() { "none".to_string() } else { current.join("; ") });
+    let answer = ask_question("Enter filters (e.g. region=Region I; year=2022), 'none' to clear, or Enter to keep: ")?;
+    if answer.eq_ignore_ascii_case("none") {
+        filters.clear();
+    } else if !answer.is_empty() {
+        match parse_filter_list(&answer) {
+            Some(parsed) => *filters = parsed,
+            None => {
+                println!("Invalid filter '{}'. Use field=value pairs separated by ';'.\n", answer);
+                return Ok(());
+            }
+        }
+    }
+
+    let current: Vec<String> = sorts.iter().map(|s| s.to_string()).collect();
+    println!("Sort a report by one of its columns instead of its usual order.");
+    println!("Current sort: {}", if current.is_empty() { "default".to_string() } else { current.join("; ") });
+    let answer = ask_question("Enter sorts (e.g. report1=TotalBudget:desc; report2=AvgDelay:asc), 'none' to clear, or Enter to keep: ")?;
+    if answer.eq_ignore_ascii_case("none") {
+        sorts.clear();
+    } else if !answer.is_empty() {
+        match parse_sort_list(&answer) {
+            Ok(parsed) => *sorts = parsed,
+            Err(e) => {
+                println!("Invalid sort: {}.\n", e);
+                return Ok(());
+            }
+        }
+    }
+
+    let (min_year, max_year) = (settings.validation.min_year, settings.validation.max_year);
+    let current_year = year.map_or(format!("{}-{}", min_year, max_year), |y| y.to_string());
+    let answer = ask_question(&format!("Report on one year ({}-{}), 'all' for every year, or Enter to keep [{}]: ", min_year, max_year, current_year))?;
+    if answer.eq_ignore_ascii_case("all") {
+        *year = None;
+    } else if !answer.is_empty() {
+        match answer.parse::<i32>() {
+            Ok(y) if (min_year..=max_year).contains(&y) => *year = Some(y),
+            _ => {
+                println!("Invalid year '{}'. Enter a year from {} to {}.\n", answer, min_year, max_year);
+                return Ok(());
+            }
+        }
+    }
+    println!();
+
+    println!("Select reports to generate:");
+    println!("[1] Regional Flood Mitigation Efficiency Summary");
+    println!("[2] Top Contractors Performance Ranking");
+    println!("[3] Annual Project Type Cost Overrun Trends");
+    println!("[6] Savings by Main Island");
+    println!("[8] Island Group Budget vs Contract Cost Waterfall");
+    println!("[9] Quarterly Trends by Start Date");
+    println!("[S] Summary statistics");
+    let answer = ask_question("Enter a comma list (e.g. 1,3) or 'all' [all]: ")?;
+    println!();
+
+    let answer = if answer.is_empty() { "all".to_string() } else { answer };
+    match ReportSelection::parse(&answer) {
+        Some(selection) => generate_reports(processed_data, settings, &selection, filters, sorts, *year, load_stages),
+        None => {
+            println!("Invalid selection '{}'. Use report numbers 1-3, 6, 8, 9, S, or 'all'.\n", answer);
+            Ok(())
+        }
+    }
+}
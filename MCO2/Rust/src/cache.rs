@@ -0,0 +1,90 @@
+//! On-disk cache of processed records, so a restart can skip re-parsing the CSV files.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Settings;
+use crate::error::PipelineError;
+use crate::io::ensure_dir;
+use crate::model::ProcessedRecord;
+
+// ============================================================================
+// PROCESSED RECORD CACHE
+// ============================================================================
+
+// Bumped whenever `ProcessedRecord` or the cache layout changes, so old caches are ignored.
+pub const CACHE_VERSION: u32 = 1;
+
+// What a cache was built from: the input files' contents and the settings that shape loading.
+// A cache is only reused when both still match.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CacheKey {
+    pub source_hash: u64,
+    pub settings_hash: u64,
+}
+
+/// Location of the cache file inside the configured output directory.
+pub fn cache_path(settings: &Settings) -> io::Result<PathBuf> {
+    Ok(settings.config.output_path()?.join(".cache").join("processed.bin"))
+}
+
+/// Builds the cache key for loading `files` with `settings`.
+/// The source hash covers every file's name and bytes, so any edit invalidates the cache.
+/// Hashes come from the standard library hasher and may change between Rust releases,
+/// which only costs one extra full load.
+pub fn cache_key(files: &[PathBuf], settings: &Settings) -> io::Result<CacheKey> {
+    let mut source = DefaultHasher::new();
+    for file in files {
+        file.hash(&mut source);
+        fs::read(file)?.hash(&mut source);
+    }
+
+    // Only the options that change which records come out of loading.
+    let mut options = DefaultHasher::new();
+    format!(
+        "{:?}|{}|{}",
+        settings.validation, settings.null_negative_delays, settings.remove_duplicates
+    )
+    .hash(&mut options);
+
+    Ok(CacheKey { source_hash: source.finish(), settings_hash: options.finish() })
+}
+
+/// Writes `records` to the cache file together with their key.
+pub fn write_cache(path: &Path, key: CacheKey, records: &[ProcessedRecord]) -> Result<(), PipelineError> {
+    ensure_dir(path)?;
+    let writer = BufWriter::new(fs::File::create(path)?);
+    bincode::serialize_into(writer, &(CACHE_VERSION, key, records)).map_err(io::Error::other)?;
+    Ok(())
+}
+
+/// Reads cached records when the cache exists and was built for `key`.
+/// A missing, outdated or unreadable cache gives `None` and the data is loaded normally.
+pub fn read_cache(path: &Path, key: CacheKey) -> Option<Vec<ProcessedRecord>> {
+    let reader = BufReader::new(fs::File::open(path).ok()?);
+    let (version, cached_key, records): (u32, CacheKey, Vec<ProcessedRecord>) = match bincode::deserialize_from(reader) {
+        Ok(contents) => contents,
+        Err(e) => {
+            debug!("ignoring unreadable cache {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    if version != CACHE_VERSION {
+        info!("Cache {} was written by another version; reloading", path.display());
+        None
+    } else if cached_key.source_hash != key.source_hash {
+        info!("Input files changed since the cache was written; reloading");
+        None
+    } else if cached_key.settings_hash != key.settings_hash {
+        info!("Loading options changed since the cache was written; reloading");
+        None
+    } else {
+        Some(records)
+    }
+}
@@ -0,0 +1,344 @@
+//! Number formatting, statistics and generic ranking helpers.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::hash::Hash;
+
+use crate::model::ProcessedRecord;
+
+// ============================================================================
+// UTILITY FUNCTIONS - COMPUTATION
+// ============================================================================
+
+/// Formats a floating-point number with a fixed number of decimal places.
+/// Non-finite values (NaN, infinity) are left blank instead of printing "NaN".
+pub fn format_number(value: f64, decimals: usize) -> String {
+    if !value.is_finite() {
+        return String::new();
+    }
+    let multiplier = 10_f64.powi(decimals as i32);
+    let rounded = (value * multiplier).round() / multiplier;
+    format!("{:.1$}", rounded, decimals)
+}
+
+/// Rounds and formats large numbers (e.g., budgets) with no decimals.
+pub fn format_large_number(value: f64) -> String {
+    if !value.is_finite() {
+        return String::new();
+    }
+    format!("{:.0}", value.round())
+}
+
+/// Inserts commas between groups of three digits, e.g. "1234567" becomes "1,234,567".
+pub fn group_thousands(digits: &str) -> String {
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Formats a peso amount like `format_large_number`, with a `₱` prefix and thousands
+/// separators, e.g. `₱5,000,000`. Negative amounts read `-₱1,500`.
+pub fn format_php_amount(value: f64) -> String {
+    let whole = format_large_number(value);
+    if whole.is_empty() {
+        return whole;
+    }
+    match whole.strip_prefix('-') {
+        Some(digits) if digits != "0" => format!("-₱{}", group_thousands(digits)),
+        Some(digits) => format!("₱{}", digits),
+        None => format!("₱{}", group_thousands(&whole)),
+    }
+}
+
+/// Calculates the median value of a slice of floats.
+pub fn calculate_median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Calculates the p-th percentile (0–100) of a slice of floats.
+/// Uses linear interpolation between ranks, so p = 50 matches `calculate_median`.
+pub fn calculate_percentile(values: &[f64], p: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let rank = (p.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// Calculates the arithmetic mean (average) of a list of floats.
+pub fn calculate_average(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+/// Calculates the sample standard deviation of a list of floats.
+/// Fewer than two values have no spread, so 0.0 is returned.
+pub fn calculate_std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = calculate_average(values);
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    variance.sqrt()
+}
+
+/// Calculates the coefficient of variation (sample standard deviation / mean), a scale-free
+/// measure of spread; values above 1.0 indicate high relative variability.
+/// Returns `None` for an empty slice or a zero mean.
+pub fn calculate_coefficient_of_variation(values: &[f64]) -> Option<f64> {
+    let mean = calculate_average(values);
+    if values.is_empty() || mean == 0.0 {
+        return None;
+    }
+    Some(calculate_std_dev(values) / mean)
+}
+
+/// Calculates the Gini coefficient (0 = perfectly equal, near 1 = maximally unequal).
+/// Uses the sorted discrete formula; empty, single-group, and all-zero inputs return 0.0.
+pub fn calculate_gini(values: &[f64]) -> f64 {
+    let total: f64 = values.iter().sum();
+    if values.len() < 2 || total == 0.0 {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let n = sorted.len() as f64;
+    let weighted_sum: f64 = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (i as f64 + 1.0) * v)
+        .sum();
+    (2.0 * weighted_sum) / (n * total) - (n + 1.0) / n
+}
+
+/// Calculates a weighted mean of `values` using the matching `weights`.
+/// Returns `None` when the slices differ in length or the weights sum to zero.
+pub fn calculate_weighted_average(values: &[f64], weights: &[f64]) -> Option<f64> {
+    if values.len() != weights.len() {
+        return None;
+    }
+    let total_weight: f64 = weights.iter().sum();
+    if total_weight == 0.0 {
+        return None;
+    }
+    let weighted_sum: f64 = values.iter().zip(weights).map(|(v, w)| v * w).sum();
+    Some(weighted_sum / total_weight)
+}
+
+/// Average completion delay where each project is weighted by its approved budget.
+/// Projects without a delay are skipped; returns 0.0 when nothing can be weighted.
+pub fn compute_budget_weighted_delay(records: &[ProcessedRecord]) -> f64 {
+    let (delays, budgets): (Vec<f64>, Vec<f64>) = records
+        .iter()
+        .filter_map(|r| r.completion_delay_days.map(|d| (d as f64, r.approved_budget_for_contract)))
+        .unzip();
+    calculate_weighted_average(&delays, &budgets).unwrap_or(0.0)
+}
+
+/// Sums `value` per funding year and returns the running total after each year.
+/// Years are sorted ascending when `sort_by_year` is set, otherwise they keep
+/// the order in which they first appear in `records`.
+pub fn cumulative_by_year<F>(records: &[ProcessedRecord], sort_by_year: bool, value: F) -> Vec<(i32, f64)>
+where
+    F: Fn(&ProcessedRecord) -> f64,
+{
+    let mut totals: Vec<(i32, f64)> = Vec::new();
+    for r in records {
+        match totals.iter_mut().find(|(year, _)| *year == r.funding_year) {
+            Some((_, total)) => *total += value(r),
+            None => totals.push((r.funding_year, value(r))),
+        }
+    }
+    if sort_by_year {
+        totals.sort_by_key(|(year, _)| *year);
+    }
+
+    let mut running = 0.0;
+    totals
+        .into_iter()
+        .map(|(year, total)| {
+            running += total;
+            (year, running)
+        })
+        .collect()
+}
+
+/// Cumulative approved budget by funding year.
+pub fn calculate_cumulative_budget(records: &[ProcessedRecord], sort_by_year: bool) -> Vec<(i32, f64)> {
+    cumulative_by_year(records, sort_by_year, |r| r.approved_budget_for_contract)
+}
+
+/// Cumulative cost savings by funding year.
+pub fn calculate_cumulative_savings(records: &[ProcessedRecord], sort_by_year: bool) -> Vec<(i32, f64)> {
+    cumulative_by_year(records, sort_by_year, |r| r.cost_savings)
+}
+
+/// Trailing moving average over `window` consecutive values.
+/// Positions before a full window is available, or whose window
+/// contains a missing (`None`) value, are `None`.
+pub fn calculate_moving_average(values: &[Option<f64>], window: usize) -> Vec<Option<f64>> {
+    (0..values.len())
+        .map(|i| {
+            if window == 0 || i + 1 < window {
+                return None;
+            }
+            let span: Option<Vec<f64>> = values[i + 1 - window..=i].iter().copied().collect();
+            span.map(|span| calculate_average(&span))
+        })
+        .collect()
+}
+
+/// Calculates the average of i64 integer values.
+pub fn calculate_average_i64(values: &[i64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<i64>() as f64 / values.len() as f64
+    }
+}
+
+/// Calculates a percentage safely (avoiding division by zero).
+pub fn calculate_percentage(part: f64, total: f64) -> f64 {
+    if total == 0.0 { 0.0 } else { (part / total) * 100.0 }
+}
+
+/// A contractor's reliability index (0-100, higher is better): the share of the contract
+/// cost saved, scaled down as the average delay approaches 90 days.
+pub fn calculate_reliability_index(total_cost: f64, total_savings: f64, avg_delay: f64) -> f64 {
+    if total_cost > 0.0 {
+        (((1.0 - (avg_delay / 90.0)).max(0.0) * (total_savings / total_cost)) * 100.0).clamp(0.0, 100.0)
+    } else {
+        0.0
+    }
+}
+
+/// Compound annual growth rate, `(end / start)^(1 / years) - 1`, as a fraction (0.10 is 10%).
+/// Returns `None` when `start_value` is not positive or `years` is not positive.
+pub fn calculate_cagr(start_value: f64, end_value: f64, years: f64) -> Option<f64> {
+    if start_value <= 0.0 || years <= 0.0 {
+        return None;
+    }
+    Some((end_value / start_value).powf(1.0 / years) - 1.0)
+}
+
+/// Growth of each region's total approved budget from its earliest to its latest funding year.
+/// Regions funded in a single year have no growth rate and are left out.
+pub fn compute_regional_budget_cagr(records: &[ProcessedRecord]) -> HashMap<String, f64> {
+    let mut budgets: HashMap<&str, BTreeMap<i32, f64>> = HashMap::new();
+    for r in records {
+        *budgets.entry(r.region.as_str()).or_default().entry(r.funding_year).or_default() += r.approved_budget_for_contract;
+    }
+    budgets
+        .into_iter()
+        .filter_map(|(region, by_year)| {
+            let (&first_year, &first_budget) = by_year.first_key_value()?;
+            let (&last_year, &last_budget) = by_year.last_key_value()?;
+            let cagr = calculate_cagr(first_budget, last_budget, (last_year - first_year) as f64)?;
+            Some((region.to_string(), cagr))
+        })
+        .collect()
+}
+
+/// Buckets items by the key `key_fn` computes for each one, keeping input order within a bucket.
+pub fn group_by<T, K, F>(items: &[T], key_fn: F) -> HashMap<K, Vec<&T>>
+where
+    K: Eq + Hash,
+    F: Fn(&T) -> K,
+{
+    let mut grouped: HashMap<K, Vec<&T>> = HashMap::new();
+    for item in items {
+        grouped.entry(key_fn(item)).or_default().push(item);
+    }
+    grouped
+}
+
+/// Returns the `n` items with the largest keys, largest first; items with equal keys keep
+/// their input order. Only `n` keys are kept on a heap, so this is O(N log n), not a full sort.
+pub fn top_n<T, K, F>(items: Vec<T>, n: usize, key_fn: F) -> Vec<T>
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    // Min-heap of the best keys so far: the root is the weakest entry and is dropped first.
+    // On equal keys the later index compares smaller, so earlier items survive.
+    let mut heap: BinaryHeap<Reverse<(K, Reverse<usize>)>> = BinaryHeap::with_capacity(n);
+    for (i, item) in items.iter().enumerate() {
+        let entry = Reverse((key_fn(item), Reverse(i)));
+        if heap.len() < n {
+            heap.push(entry);
+        } else if let Some(mut weakest) = heap.peek_mut()
+            && entry < *weakest
+        {
+            // Beats the weakest kept entry: replace it in place instead of a push and a pop.
+            *weakest = entry;
+        }
+    }
+
+    let mut slots: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    heap.into_sorted_vec()
+        .into_iter()
+        .filter_map(|Reverse((_, Reverse(i)))| slots[i].take())
+        .collect()
+}
+
+/// Sorts `slice` by several keys: the first key function decides, and each later one
+/// only breaks ties left by those before it. The sort is stable.
+pub fn sort_by_multiple_keys<T, F, K>(slice: &mut [T], key_fns: &[F])
+where
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    slice.sort_by(|a, b| {
+        key_fns
+            .iter()
+            .map(|key_fn| key_fn(a).cmp(&key_fn(b)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+// Orders `f64` values with `total_cmp` so they can be used as `Ord` keys.
+// Equality follows the same order, so NaN equals NaN and -0.0 differs from 0.0.
+pub struct OrdF64(pub f64);
+
+impl PartialEq for OrdF64 {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.total_cmp(&other.0) == Ordering::Equal
+    }
+}
+
+impl Eq for OrdF64 {}
+
+impl PartialOrd for OrdF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrdF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
@@ -0,0 +1,673 @@
+//! Command-line settings, `config.toml` output locations and validation rules.
+
+use std::cell::Cell as FlagCell;
+use std::env;
+use std::fs::{self, create_dir_all};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::prelude::*;
+use log::{info, warn, LevelFilter};
+use serde::Deserialize;
+
+use crate::error::PipelineError;
+use crate::model::{OutputFormat, ProcessedRecord};
+use crate::io::{data_dir, ask_question};
+use crate::reports::report_headers;
+
+// ============================================================================
+// SETUP AND CONFIGURATION
+// ============================================================================
+
+// First and last funding years covered by the analysis.
+pub const START_YEAR: i32 = 2021;
+pub const END_YEAR: i32 = 2023;
+
+// Bounds for budgets and contract costs: at least one centavo, at most 10 billion PHP
+// (the upper bound can be overridden in `validation.toml`).
+pub const MIN_CONTRACT_VALUE: f64 = 0.01;
+pub const MAX_CONTRACT_VALUE: f64 = 10_000_000_000.0;
+
+// Date layouts accepted by default: ISO first, then the forms used in
+// government documents (03/18/2021, 18-Mar-2021, March 18, 2021).
+pub const DATE_FORMATS: [&str; 4] = ["%Y-%m-%d", "%m/%d/%Y", "%d-%b-%Y", "%B %d, %Y"];
+
+// Controls how the console preview table of each report is rendered.
+pub struct PreviewConfig {
+    // Number of rows shown in the preview (0 skips the preview entirely).
+    pub max_rows: usize,
+    // Whether to print how many rows were left out of the preview.
+    pub show_row_count: bool,
+    // Whether to print each column's maximum character width in the header.
+    pub show_column_widths: bool,
+    // Whether preview cells may use terminal colors (off when stdout is not a terminal).
+    pub use_color: bool,
+    // Replaces the preview with a single line per report (path and row count).
+    pub quiet: bool,
+    // Longest text shown in one preview cell; longer values end with "…".
+    pub max_column_width: usize,
+}
+
+impl PreviewConfig {
+    /// Builds the preview settings, reading `max_rows` from `DPWH_PREVIEW_ROWS` (default 5)
+    /// and `max_column_width` from `DPWH_PREVIEW_COL_WIDTH` (default 30).
+    pub fn from_env() -> Self {
+        let max_rows = env::var("DPWH_PREVIEW_ROWS")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .unwrap_or(5);
+        let max_column_width = env::var("DPWH_PREVIEW_COL_WIDTH")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|&w| w > 0)
+            .unwrap_or(30);
+        PreviewConfig {
+            max_rows,
+            show_row_count: true,
+            show_column_widths: false,
+            use_color: io::stdout().is_terminal(),
+            quiet: false,
+            max_column_width,
+        }
+    }
+}
+
+// Controls which output files are written and how.
+#[derive(Clone)]
+pub struct OutputSettings {
+    // Set by `--dry-run`: build and preview everything but write no files.
+    pub dry_run: bool,
+    // Set by `--excel-compat`: written CSVs get a UTF-8 BOM and CRLF line endings.
+    pub excel_compat: bool,
+    // Set by `--format`: whether reports are written as CSV/TSV, JSON, or both.
+    pub format: OutputFormat,
+    // Set by `--write-empty`: a report with no rows still gets header-only files instead of being skipped.
+    pub write_empty: bool,
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        OutputSettings { dry_run: false, excel_compat: false, format: OutputFormat::Both, write_empty: false }
+    }
+}
+
+// Output locations read from an optional `config.toml` in the working directory.
+// Any key left out of the file keeps its default value.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct Config {
+    pub output_dir: String,
+    pub report1_file: String,
+    pub report2_file: String,
+    pub report3_file: String,
+    pub report6_file: String,
+    pub report8_file: String,
+    pub report9_file: String,
+    pub summary_file: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            output_dir: "output".to_string(),
+            report1_file: "report1_regional_efficiency.csv".to_string(),
+            report2_file: "report2_contractor_ranking.csv".to_string(),
+            report3_file: "report3_cost_overrun_trends.csv".to_string(),
+            report6_file: "report6_island_summary.csv".to_string(),
+            report8_file: "report8_island_budget_waterfall.csv".to_string(),
+            report9_file: "report9_quarterly_trends.csv".to_string(),
+            summary_file: "summary.json".to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Resolves the configured output directory against the working directory.
+    pub fn output_path(&self) -> io::Result<PathBuf> {
+        Ok(env::current_dir()?.join(&self.output_dir))
+    }
+
+    /// Creates a `run_YYYYMMDD_HHMMSS` directory inside the output directory and
+    /// records its name in `latest` so scripts can find the newest run.
+    /// A dry run only works out the path.
+    pub fn create_run_dir(&self, dry_run: bool) -> io::Result<PathBuf> {
+        let base = self.output_path()?;
+        let name = Local::now().format("run_%Y%m%d_%H%M%S").to_string();
+        let run_dir = base.join(&name);
+        if dry_run {
+            return Ok(run_dir);
+        }
+        create_dir_all(&run_dir)?;
+        fs::write(base.join("latest"), format!("{}\n", name))?;
+        Ok(run_dir)
+    }
+
+    /// The newest run directory named in `latest`, if there is one.
+    pub fn latest_run_path(&self) -> io::Result<Option<PathBuf>> {
+        let base = self.output_path()?;
+        Ok(fs::read_to_string(base.join("latest"))
+            .ok()
+            .map(|name| base.join(name.trim()))
+            .filter(|dir| dir.is_dir()))
+    }
+}
+
+/// Loads `config.toml` from the working directory, falling back to defaults
+/// when the file is absent or cannot be parsed.
+pub fn load_config() -> Config {
+    let Ok(path) = env::current_dir().map(|dir| dir.join("config.toml")) else {
+        return Config::default();
+    };
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Config::default();
+    };
+    match toml::from_str(&contents) {
+        Ok(config) => {
+            info!("Loaded configuration from {}", path.display());
+            config
+        }
+        Err(e) => {
+            warn!("could not parse {} ({}); using defaults", path.display(), e);
+            Config::default()
+        }
+    }
+}
+
+// Which outputs a report run should produce.
+#[derive(Clone)]
+pub struct ReportSelection {
+    pub report1: bool,
+    pub report2: bool,
+    pub report3: bool,
+    pub report6: bool,
+    pub report8: bool,
+    pub report9: bool,
+    pub summary: bool,
+}
+
+impl ReportSelection {
+    /// Selects every report plus the summary.
+    pub fn all() -> Self {
+        ReportSelection {
+            report1: true,
+            report2: true,
+            report3: true,
+            report6: true,
+            report8: true,
+            report9: true,
+            summary: true,
+        }
+    }
+
+    /// Parses a comma list such as "1,3" or "2,s"; "all" selects everything.
+    /// Returns `None` if any item is unrecognized or nothing was selected.
+    pub fn parse(input: &str) -> Option<Self> {
+        let mut selection = ReportSelection {
+            report1: false,
+            report2: false,
+            report3: false,
+            report6: false,
+            report8: false,
+            report9: false,
+            summary: false,
+        };
+        for item in input.split(',').map(|i| i.trim().to_lowercase()) {
+            match item.as_str() {
+                "all" => selection = ReportSelection::all(),
+                "1" => selection.report1 = true,
+                "2" => selection.report2 = true,
+                "3" => selection.report3 = true,
+                "6" => selection.report6 = true,
+                "8" => selection.report8 = true,
+                "9" => selection.report9 = true,
+                "s" | "summary" => selection.summary = true,
+                "" => {}
+                _ => return None,
+            }
+        }
+        let any = selection.report1
+            || selection.report2
+            || selection.report3
+            || selection.report6
+            || selection.report8
+            || selection.report9
+            || selection.summary;
+        any.then_some(selection)
+    }
+}
+
+// One condition used to narrow the loaded records before generating reports.
+// Text fields match case-insensitively; contractor matches on a substring.
+#[derive(Clone)]
+pub enum RecordFilter {
+    Region(String),
+    Province(String),
+    MainIsland(String),
+    FundingYear(i32),
+    Contractor(String),
+    TypeOfWork(String),
+}
+
+impl RecordFilter {
+    /// Parses a `field=value` specification such as "region=Region I" or "year=2022".
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (field, value) = spec.split_once('=')?;
+        let value = value.trim().to_string();
+        if value.is_empty() {
+            return None;
+        }
+        match field.trim().to_lowercase().as_str() {
+            "region" => Some(RecordFilter::Region(value)),
+            "province" => Some(RecordFilter::Province(value)),
+            "island" | "mainisland" | "main_island" => Some(RecordFilter::MainIsland(value)),
+            "year" | "fundingyear" | "funding_year" => value.parse().ok().map(RecordFilter::FundingYear),
+            "contractor" => Some(RecordFilter::Contractor(value)),
+            "type" | "typeofwork" | "type_of_work" => Some(RecordFilter::TypeOfWork(value)),
+            _ => None,
+        }
+    }
+
+    /// Checks whether a record satisfies this condition.
+    pub fn matches(&self, record: &ProcessedRecord) -> bool {
+        match self {
+            RecordFilter::Region(v) => record.region.eq_ignore_ascii_case(v),
+            RecordFilter::Province(v) => record.province.eq_ignore_ascii_case(v),
+            RecordFilter::MainIsland(v) => record.main_island.eq_ignore_ascii_case(v),
+            RecordFilter::FundingYear(y) => record.funding_year == *y,
+            RecordFilter::Contractor(v) => record.contractor.to_lowercase().contains(&v.to_lowercase()),
+            RecordFilter::TypeOfWork(v) => record.type_of_work.eq_ignore_ascii_case(v),
+        }
+    }
+}
+
+impl std::fmt::Display for RecordFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RecordFilter::Region(v) => write!(f, "region={}", v),
+            RecordFilter::Province(v) => write!(f, "province={}", v),
+            RecordFilter::MainIsland(v) => write!(f, "island={}", v),
+            RecordFilter::FundingYear(y) => write!(f, "year={}", y),
+            RecordFilter::Contractor(v) => write!(f, "contractor={}", v),
+            RecordFilter::TypeOfWork(v) => write!(f, "type={}", v),
+        }
+    }
+}
+
+/// Parses a semicolon-separated list of filters such as "region=Region I; year=2022".
+pub fn parse_filter_list(input: &str) -> Option<Vec<RecordFilter>> {
+    input
+        .split(';')
+        .filter(|spec| !spec.trim().is_empty())
+        .map(RecordFilter::parse)
+        .collect()
+}
+
+// Orders one report's rows by a column instead of the report's built-in ranking,
+// e.g. `report1=TotalBudget:desc`. Reports without one keep their default order.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReportSort {
+    // Menu number of the report: "1", "2", "3", "6", "8" or "9".
+    pub report: String,
+    // One of the report's column headers, spelled as in its CSV.
+    pub column: String,
+    pub descending: bool,
+}
+
+impl ReportSort {
+    /// Parses `reportN=Column[:asc|desc]`, such as "report1=TotalBudget:desc"; "1=TotalBudget" also works.
+    /// The column is matched case-insensitively against that report's headers; the direction defaults to ascending.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (report, rest) = spec.split_once('=').ok_or_else(|| format!("'{}' is not reportN=Column[:asc|desc]", spec.trim()))?;
+        let report = report.trim().to_lowercase();
+        let report = report.strip_prefix("report").unwrap_or(&report).to_string();
+        let headers = report_headers(&report).ok_or_else(|| format!("there is no report '{}' to sort (use 1, 2, 3, 6, 8 or 9)", report))?;
+
+        let (column, direction) = rest.split_once(':').unwrap_or((rest, "asc"));
+        let descending = match direction.trim().to_lowercase().as_str() {
+            "asc" => false,
+            "desc" => true,
+            other => return Err(format!("sort direction '{}' must be asc or desc", other)),
+        };
+        let column = headers
+            .iter()
+            .find(|h| h.eq_ignore_ascii_case(column.trim()))
+            .ok_or_else(|| format!("report {} has no column '{}' (columns: {})", report, column.trim(), headers.join(", ")))?;
+        Ok(ReportSort { report, column: column.to_string(), descending })
+    }
+}
+
+impl std::fmt::Display for ReportSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "report{}={}:{}", self.report, self.column, if self.descending { "desc" } else { "asc" })
+    }
+}
+
+/// Parses a semicolon-separated list of sorts such as "report1=TotalBudget:desc; report2=AvgDelay".
+pub fn parse_sort_list(input: &str) -> Result<Vec<ReportSort>, String> {
+    input
+        .split(';')
+        .filter(|spec| !spec.trim().is_empty())
+        .map(ReportSort::parse)
+        .collect()
+}
+
+// Rules applied when validating raw records.
+// Loaded from an optional `validation.toml` in the data directory; missing keys keep their defaults.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ValidationConfig {
+    pub min_year: i32,
+    pub max_year: i32,
+    pub required_fields: Vec<String>,
+    pub lat_range: (f64, f64),
+    pub lon_range: (f64, f64),
+    // Largest believable budget or contract cost, in PHP.
+    pub max_contract_value: f64,
+    // chrono format strings tried in order when parsing dates.
+    pub date_formats: Vec<String>,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        ValidationConfig {
+            min_year: START_YEAR,
+            max_year: END_YEAR,
+            required_fields: ["Region", "MainIsland", "ApprovedBudgetForContract", "ContractCost"]
+                .iter()
+                .map(|f| f.to_string())
+                .collect(),
+            lat_range: (-90.0, 90.0),
+            lon_range: (-180.0, 180.0),
+            max_contract_value: MAX_CONTRACT_VALUE,
+            date_formats: DATE_FORMATS.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+}
+
+/// Loads `validation.toml` from the data directory, falling back to defaults
+/// when the file is absent or cannot be parsed.
+pub fn load_validation_config() -> ValidationConfig {
+    let path = data_dir().join("validation.toml");
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return ValidationConfig::default();
+    };
+    match toml::from_str(&contents) {
+        Ok(config) => {
+            info!("Loaded validation rules from {}", path.display());
+            config
+        }
+        Err(e) => {
+            warn!("could not parse {} ({}); using default rules", path.display(), e);
+            ValidationConfig::default()
+        }
+    }
+}
+
+// Runtime options controlled through command-line flags.
+pub struct Settings {
+    // Whether delays of projects completed before they started are nulled out.
+    pub null_negative_delays: bool,
+    // Whether duplicate projects are dropped while loading.
+    pub remove_duplicates: bool,
+    // Console preview options for the generated reports.
+    pub preview: PreviewConfig,
+    // Which output files are written and how.
+    pub output: OutputSettings,
+    // Whether CSV rows are cleaned while reading instead of keeping all raw records.
+    pub stream: bool,
+    // Output directory and filenames.
+    pub config: Config,
+    // Number of contractors kept in Report 2 (0 keeps all of them).
+    pub top_n: usize,
+    // Reports requested with `--reports`; when set the program runs in batch mode.
+    pub reports: Option<ReportSelection>,
+    // Whether "Unknown" contractors are ranked in Report 2 (labelled as unreliable).
+    pub include_unknown_contractors: bool,
+    // Filters given with `--filter field=value`; all of them must match.
+    pub filters: Vec<RecordFilter>,
+    // Report orders given with `--sort reportN=Column:desc`; the last one given for a report wins.
+    pub sorts: Vec<ReportSort>,
+    // Validation rules for raw records.
+    pub validation: ValidationConfig,
+    // Whether each report run writes into its own timestamped subdirectory.
+    pub versioned_output: bool,
+    // Decides whether existing output files may be replaced.
+    pub overwrite: OverwriteGuard,
+    // Decimal places for the total budget, cost and savings columns (`--budget-decimals`).
+    pub budget_decimals: usize,
+    // Single funding year the reports are narrowed to (`--year`); None covers the whole range.
+    pub report_year: Option<i32>,
+    // Files, directories or glob patterns given with `--input`, `--csv` or `--csv-dir`;
+    // empty means the default dataset.
+    pub inputs: Vec<String>,
+    // Whether loaded records are cached in `output/.cache` and reused on the next run (off with `--no-cache`).
+    pub use_cache: bool,
+    // Longest time reading the input files may take (`--load-timeout` seconds); None waits forever.
+    pub load_timeout: Option<Duration>,
+    // Years in Report 3's moving average of savings (`--moving-average`, 2 or 3).
+    pub moving_average_years: usize,
+    // Whether per-stage timings are printed and added to the summary (`--timings`; always on in batch mode).
+    pub timings: bool,
+    // Output directory to check against its manifest (`--verify-manifest [DIR]`) instead of running the pipeline.
+    pub verify_manifest: Option<PathBuf>,
+    // Whether a batch run also writes the cleaned and median-imputed records (`--export-cleaned`).
+    pub export_cleaned: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            null_negative_delays: true,
+            remove_duplicates: true,
+            preview: PreviewConfig::from_env(),
+            output: OutputSettings::default(),
+            stream: false,
+            config: Config::default(),
+            top_n: 15,
+            reports: None,
+            include_unknown_contractors: false,
+            filters: Vec::new(),
+            sorts: Vec::new(),
+            validation: ValidationConfig::default(),
+            versioned_output: false,
+            overwrite: OverwriteGuard::default(),
+            budget_decimals: 0,
+            report_year: None,
+            inputs: Vec::new(),
+            use_cache: true,
+            load_timeout: None,
+            moving_average_years: 3,
+            timings: false,
+            verify_manifest: None,
+            export_cleaned: false,
+        }
+    }
+}
+
+// Guards existing output files against being silently replaced.
+// Interactive runs ask per file ("all" covers the rest of the run);
+// batch runs need `--force`, otherwise conflicts are reported up front.
+#[derive(Default)]
+pub struct OverwriteGuard {
+    // Whether the user can be asked before a file is replaced.
+    pub interactive: bool,
+    // Set by `--force`: replace files without asking.
+    pub force: bool,
+    // Set once the user answers "all" to an overwrite prompt.
+    pub overwrite_all: FlagCell<bool>,
+}
+
+impl OverwriteGuard {
+    /// Returns whether `path` may be written, asking the user if it already exists.
+    /// Without a user to ask, an existing file is an `OutputExists` error.
+    pub fn confirm(&self, path: &Path) -> Result<bool, PipelineError> {
+        if self.force || self.overwrite_all.get() || !path.exists() {
+            return Ok(true);
+        }
+        if !self.interactive {
+            return Err(PipelineError::OutputExists { paths: vec![path.to_path_buf()] });
+        }
+        loop {
+            let prompt = format!("File {} exists, overwrite? (Y/N/all) [N]: ", path.display());
+            match ask_question(&prompt)?.to_lowercase().as_str() {
+                "y" | "yes" => return Ok(true),
+                "" | "n" | "no" => return Ok(false),
+                "a" | "all" => {
+                    self.overwrite_all.set(true);
+                    return Ok(true);
+                }
+                _ => println!("Please answer Y, N, or all."),
+            }
+        }
+    }
+
+    /// Returns whether every path in `paths` may be written; stops at the first refusal.
+    pub fn confirm_all(&self, paths: &[PathBuf]) -> Result<bool, PipelineError> {
+        for path in paths {
+            if !self.confirm(path)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Parses command-line flags into `Settings`, ignoring unknown ones with a warning.
+pub fn parse_args() -> Settings {
+    // Set up logging first so loading the config files can already log.
+    let args: Vec<String> = env::args().skip(1).collect();
+    let verbosity = args.iter().map(|arg| match arg.as_str() {
+        "-v" | "--verbose" => 1,
+        "-vv" => 2,
+        _ => 0,
+    }).sum();
+    init_logging(verbosity);
+
+    let mut settings = Settings {
+        config: load_config(),
+        validation: load_validation_config(),
+        ..Settings::default()
+    };
+    let mut show_previews = false;
+    let mut args = args.into_iter().peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-v" | "-vv" | "--verbose" => {}
+            "--keep-negative-delays" => settings.null_negative_delays = false,
+            "--keep-duplicates" => settings.remove_duplicates = false,
+            "--no-cache" => settings.use_cache = false,
+            "--include-unknown-contractors" => settings.include_unknown_contractors = true,
+            "--filter" => match args.next().as_deref().and_then(RecordFilter::parse) {
+                Some(filter) => settings.filters.push(filter),
+                None => warn!("--filter expects field=value (region, province, island, year, contractor, type); ignoring"),
+            },
+            "--sort" => match ReportSort::parse(&args.next().unwrap_or_default()) {
+                Ok(sort) => settings.sorts.push(sort),
+                Err(e) => warn!("--sort: {}; ignoring", e),
+            },
+            "--show-column-widths" => settings.preview.show_column_widths = true,
+            "--hide-row-count" => settings.preview.show_row_count = false,
+            "--stream" => settings.stream = true,
+            "--timings" => settings.timings = true,
+            "--export-cleaned" => settings.export_cleaned = true,
+            "--versioned-output" => settings.versioned_output = true,
+            "--force" => settings.overwrite.force = true,
+            "--no-color" => settings.preview.use_color = false,
+            "--quiet" => settings.preview.quiet = true,
+            "--dry-run" => settings.output.dry_run = true,
+            "--excel-compat" => settings.output.excel_compat = true,
+            "--write-empty" => settings.output.write_empty = true,
+            "--format" => match args.next().as_deref().and_then(OutputFormat::parse) {
+                Some(format) => settings.output.format = format,
+                None => warn!("--format expects csv, json, or both; writing both"),
+            },
+            "--show-previews" => show_previews = true,
+            "--reports" => match args.next().as_deref().and_then(ReportSelection::parse) {
+                Some(selection) => settings.reports = Some(selection),
+                None => warn!("--reports expects a list like 1,3 or all; ignoring"),
+            },
+            "--max-col-width" => match args.next().and_then(|v| v.parse::<usize>().ok()).filter(|&w| w > 0) {
+                Some(width) => settings.preview.max_column_width = width,
+                None => warn!(
+                    "--max-col-width expects a positive number; keeping {}",
+                    settings.preview.max_column_width
+                ),
+            },
+            "--budget-decimals" => match args.next().and_then(|v| v.parse::<usize>().ok()) {
+                Some(decimals) => settings.budget_decimals = decimals,
+                None => warn!(
+                    "--budget-decimals expects a non-negative number; keeping {}",
+                    settings.budget_decimals
+                ),
+            },
+            "--year" => match args.next().and_then(|v| v.parse::<i32>().ok()) {
+                Some(year) => settings.report_year = Some(year),
+                None => warn!("--year expects a funding year like 2022; reporting on all years"),
+            },
+            "--moving-average" => match args.next().and_then(|v| v.parse::<usize>().ok()).filter(|n| (2..=3).contains(n)) {
+                Some(years) => settings.moving_average_years = years,
+                None => warn!(
+                    "--moving-average expects 2 or 3 years; keeping {}",
+                    settings.moving_average_years
+                ),
+            },
+            // An unquoted `--input data/*.csv` reaches us already expanded by the shell,
+            // so every value up to the next flag belongs to it. `--csv` is the same flag.
+            "--input" | "--csv" => {
+                let first = settings.inputs.len();
+                while let Some(value) = args.next_if(|v| !v.starts_with('-')) {
+                    settings.inputs.push(value);
+                }
+                if settings.inputs.len() == first {
+                    warn!("{} expects a CSV file, directory, or glob pattern like data/*.csv; ignoring", arg);
+                }
+            }
+            "--csv-dir" => match args.next() {
+                Some(dir) => settings.inputs.push(dir),
+                None => warn!("--csv-dir expects a directory of CSV files; ignoring"),
+            },
+            "--load-timeout" => match args.next().and_then(|v| v.parse::<f64>().ok()).filter(|s| *s > 0.0 && s.is_finite()) {
+                Some(seconds) => settings.load_timeout = Some(Duration::from_secs_f64(seconds)),
+                None => warn!("--load-timeout expects a number of seconds greater than 0; waiting without a limit"),
+            },
+            // The directory is optional and defaults to the configured output directory.
+            "--verify-manifest" => {
+                let dir = args.next_if(|v| !v.starts_with('-')).unwrap_or_else(|| settings.config.output_dir.clone());
+                settings.verify_manifest = Some(PathBuf::from(dir));
+            }
+            "--top-n" => match args.next().and_then(|v| v.parse::<usize>().ok()) {
+                Some(n) => settings.top_n = n,
+                None => warn!("--top-n expects a non-negative number; keeping {}", settings.top_n),
+            },
+            _ => warn!("ignoring unrecognized option '{}'", arg),
+        }
+    }
+
+    // Batch runs are usually scripted, so previews are off unless asked for.
+    if settings.reports.is_some() && !show_previews {
+        settings.preview.quiet = true;
+    }
+    // Batch runs always report timings; interactive runs only with `--timings`.
+    if settings.reports.is_some() {
+        settings.timings = true;
+    }
+    // Only interactive runs can stop and ask before replacing a file.
+    settings.overwrite.interactive = settings.reports.is_none();
+    settings
+}
+
+/// Sends log messages to stderr: info and above by default, debug with `-v`,
+/// trace with `-vv`. A `RUST_LOG` setting overrides the flags.
+pub fn init_logging(verbosity: usize) {
+    let level = match verbosity {
+        0 => LevelFilter::Info,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_default_env()
+        .format(|buf, record| writeln!(buf, "[{}] {}", record.level(), record.args()))
+        .init();
+}
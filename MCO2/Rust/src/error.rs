@@ -0,0 +1,160 @@
+//! The error type returned by the loading, report and file-writing steps.
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// ============================================================================
+// PIPELINE ERRORS
+// ============================================================================
+
+// Everything that can stop the pipeline, kept apart so callers can react to each case.
+#[derive(Debug)]
+pub enum PipelineError {
+    // Reading or writing a file failed.
+    Io(io::Error),
+    // The dataset was not found in any of the searched data directories.
+    DataFileNotFound { searched: Vec<PathBuf> },
+    // An input file, directory or glob pattern (`--input`, `--csv`, `--csv-dir`) named no CSV files.
+    InputNotFound { pattern: String },
+    // A CSV row could not be parsed; `row` is the 1-based line in the file.
+    CsvParse { file: String, row: u64, source: csv::Error },
+    // The CSV header row lacks columns the records need.
+    MissingHeaders { file: String, missing: Vec<String> },
+    // There is nothing to report on, e.g. the files have no data rows.
+    EmptyDataset { reason: String },
+    // Every row was rejected while validating.
+    NoValidRecords { total: usize },
+    // Reports were requested before any data was loaded.
+    NoDataLoaded,
+    // A batch run would replace existing output files without `--force`.
+    OutputExists { paths: Vec<PathBuf> },
+    // The output directory cannot be created or written to, e.g. a read-only mount.
+    OutputNotWritable { dir: PathBuf, source: io::Error },
+    // `--verify-manifest` found files that are missing or changed since the manifest was written.
+    ManifestMismatch { count: usize },
+    // Reading the input files took longer than `--load-timeout`.
+    Timeout(Duration),
+}
+
+impl PipelineError {
+    /// Attaches the name of the CSV file to a parse error raised while reading it.
+    pub fn in_file(self, path: &Path) -> Self {
+        match self {
+            PipelineError::CsvParse { row, source, .. } => PipelineError::CsvParse {
+                file: path.display().to_string(),
+                row,
+                source,
+            },
+            other => other,
+        }
+    }
+
+    /// Whether the interactive menu can report this error and carry on.
+    /// These all mean "nothing to analyze" rather than a broken file or disk.
+    pub fn is_recoverable(&self) -> bool {
+        matches!(
+            self,
+            PipelineError::EmptyDataset { .. } | PipelineError::NoValidRecords { .. } | PipelineError::NoDataLoaded
+        )
+    }
+
+    /// Whether stdin was closed while a prompt was waiting for an answer.
+    pub fn is_end_of_input(&self) -> bool {
+        matches!(self, PipelineError::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof)
+    }
+
+    /// Process exit status for this error, following the BSD `sysexits.h` codes:
+    /// 64 usage, 65 bad data, 66 missing input, 73 cannot create output, 74 I/O error,
+    /// 75 temporary failure (worth retrying).
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            PipelineError::NoDataLoaded => 64,
+            PipelineError::CsvParse { .. }
+            | PipelineError::MissingHeaders { .. }
+            | PipelineError::EmptyDataset { .. }
+            | PipelineError::NoValidRecords { .. }
+            | PipelineError::ManifestMismatch { .. } => 65,
+            PipelineError::DataFileNotFound { .. } | PipelineError::InputNotFound { .. } => 66,
+            PipelineError::OutputExists { .. } | PipelineError::OutputNotWritable { .. } => 73,
+            PipelineError::Io(_) => 74,
+            PipelineError::Timeout(_) => 75,
+        }
+    }
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PipelineError::Io(e) => write!(f, "{}", e),
+            PipelineError::DataFileNotFound { searched } => {
+                let dirs: Vec<String> = searched.iter().map(|d| d.display().to_string()).collect();
+                write!(
+                    f,
+                    "CSV file not found: dpwh_flood_control_projects.csv (or .csv.gz); looked in {}",
+                    dirs.join(", ")
+                )
+            }
+            PipelineError::InputNotFound { pattern } => write!(f, "no CSV files found for input '{}'", pattern),
+            PipelineError::CsvParse { file, row, source } if file.is_empty() => {
+                write!(f, "CSV line {}: {}", row, source)
+            }
+            PipelineError::CsvParse { file, row, source } => write!(f, "{}, line {}: {}", file, row, source),
+            PipelineError::MissingHeaders { file, missing } => {
+                write!(f, "{} is missing required column(s): {}", file, missing.join(", "))
+            }
+            PipelineError::EmptyDataset { reason } => write!(f, "No valid records to process ({})", reason),
+            PipelineError::NoValidRecords { total } => {
+                write!(f, "No valid records to process (all {} rows failed validation)", total)
+            }
+            PipelineError::NoDataLoaded => write!(f, "No data loaded. Please load the file first (option 1)."),
+            PipelineError::OutputExists { paths } => {
+                let files: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+                write!(f, "these output files already exist: {}", files.join(", "))
+            }
+            PipelineError::OutputNotWritable { dir, source } => {
+                write!(f, "cannot write to output directory {}: {}", dir.display(), source)
+            }
+            PipelineError::ManifestMismatch { count } => {
+                write!(f, "{} file(s) do not match the manifest", count)
+            }
+            PipelineError::Timeout(limit) => write!(
+                f,
+                "reading the input files took longer than {:?}; is the data on an unreachable network drive?",
+                limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PipelineError::Io(e) => Some(e),
+            PipelineError::CsvParse { source, .. } => Some(source),
+            PipelineError::OutputNotWritable { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PipelineError {
+    fn from(e: io::Error) -> Self {
+        PipelineError::Io(e)
+    }
+}
+
+// I/O failures inside the csv crate stay `Io`; everything else is a parse error.
+impl From<csv::Error> for PipelineError {
+    fn from(e: csv::Error) -> Self {
+        if !e.is_io_error() {
+            let row = e.position().map_or(0, |p| p.line());
+            return PipelineError::CsvParse { file: String::new(), row, source: e };
+        }
+        match e.into_kind() {
+            csv::ErrorKind::Io(io_err) => PipelineError::Io(io_err),
+            kind => PipelineError::Io(io::Error::other(format!("{:?}", kind))),
+        }
+    }
+}
@@ -0,0 +1,557 @@
+//! Reading input CSVs, writing report files and console prompts.
+
+use std::env;
+use std::fs::{self, create_dir_all};
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use csv::{Reader, ReaderBuilder, WriterBuilder};
+use flate2::read::GzDecoder;
+use log::info;
+use serde_json::Value as JsonValue;
+use rusqlite::{params, Connection};
+
+use crate::error::PipelineError;
+use crate::model::{RawRecord, CleanedRecord, ProcessedRecord, ReportRow, Delimiter, ValidationLog};
+use crate::config::ValidationConfig;
+use crate::validate::clean_raw_record;
+use crate::transform::add_derived_fields;
+
+// ============================================================================
+// UTILITY FUNCTIONS - FILE OPERATIONS
+// ============================================================================
+
+// Rows between progress updates while reading and cleaning.
+pub const PROGRESS_INTERVAL: usize = 10_000;
+
+// Byte-order mark Excel puts at the start of "CSV UTF-8" exports.
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+// Row counter printed while a long loop runs.
+// On a terminal the line is redrawn in place; otherwise plain lines are printed.
+pub struct Progress {
+    pub label: String,
+    pub count: usize,
+    pub redraw: bool,
+}
+
+impl Progress {
+    pub fn new(label: &str) -> Self {
+        Progress {
+            label: label.to_string(),
+            count: 0,
+            redraw: io::stdout().is_terminal(),
+        }
+    }
+
+    /// Counts one row, printing an update every `PROGRESS_INTERVAL` rows.
+    pub fn tick(&mut self) {
+        self.count += 1;
+        if self.count.is_multiple_of(PROGRESS_INTERVAL) {
+            if self.redraw {
+                print!("\r{}: {} rows...", self.label, self.count);
+                let _ = io::stdout().flush();
+            } else {
+                println!("{}: {} rows...", self.label, self.count);
+            }
+        }
+    }
+
+    /// Clears the redrawn line once the loop is done.
+    pub fn finish(&self) {
+        if self.redraw && self.count >= PROGRESS_INTERVAL {
+            print!("\r{}\r", " ".repeat(self.label.len() + 24));
+            let _ = io::stdout().flush();
+        }
+    }
+}
+
+/// Ensures the directory for a file path exists.
+/// Creates directories if they don't exist.
+pub fn ensure_dir(file_path: &Path) -> io::Result<()> {
+    if let Some(dir) = file_path.parent()
+        && !dir.exists()
+    {
+        create_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+/// Checks that files can be written to `dir` by creating it and a small probe
+/// file, which is removed again. Run before a batch of writes so a read-only
+/// mount fails before any file is written rather than halfway through.
+pub fn check_writable(dir: &Path) -> io::Result<()> {
+    create_dir_all(dir)?;
+    let probe = dir.join(format!(".write_probe_{}", std::process::id()));
+    fs::write(&probe, b"")?;
+    fs::remove_file(&probe)
+}
+
+/// Places a `data/` directory may live, in the order they are searched:
+/// the working directory, the executable's directory, and (in debug builds
+/// only) the crate's source tree.
+pub fn data_dir_candidates() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Ok(cwd) = env::current_dir() {
+        candidates.push(cwd.join("data"));
+    }
+    if let Some(exe_dir) = env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        candidates.push(exe_dir.join("data"));
+    }
+    if cfg!(debug_assertions) {
+        candidates.push(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("data"));
+    }
+    candidates
+}
+
+/// Returns the `data/` directory that holds the input datasets: the first
+/// candidate that exists, or `./data` when none do.
+pub fn data_dir() -> PathBuf {
+    let candidates = data_dir_candidates();
+    candidates
+        .iter()
+        .find(|dir| dir.is_dir())
+        .or(candidates.first())
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from("data"))
+}
+
+/// Locates the target CSV dataset in the first `data/` directory that has it.
+/// Falls back to the gzipped `.csv.gz` export when the plain CSV is absent.
+pub fn find_csv_file() -> Result<PathBuf, PipelineError> {
+    let searched = data_dir_candidates();
+    for dir in &searched {
+        let file_path = dir.join("dpwh_flood_control_projects.csv");
+        let gz_path = dir.join("dpwh_flood_control_projects.csv.gz");
+        if file_path.exists() {
+            return Ok(file_path);
+        } else if gz_path.exists() {
+            return Ok(gz_path);
+        }
+    }
+    Err(PipelineError::DataFileNotFound { searched })
+}
+
+/// Whether a path names a gzipped file (`.gz` extension).
+pub fn is_gzipped(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// Opens a CSV reader, decompressing on the fly when the file is gzipped.
+/// Fails with `MissingHeaders` when the header row lacks any `RawRecord` column.
+pub fn open_csv_reader(file_path: &Path) -> Result<Reader<Box<dyn Read>>, PipelineError> {
+    let file = fs::File::open(file_path)?;
+    let input: Box<dyn Read> = if is_gzipped(file_path) {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    let input: Box<dyn Read> = Box::new(skip_utf8_bom(io::BufReader::new(input))?);
+    let mut rdr = ReaderBuilder::new().from_reader(input);
+    let headers = rdr.headers().map_err(|e| PipelineError::from(e).in_file(file_path))?;
+    let missing: Vec<String> = RawRecord::HEADERS
+        .iter()
+        .filter(|&&name| !headers.iter().any(|h| h.trim() == name))
+        .map(|name| name.to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(PipelineError::MissingHeaders { file: file_path.display().to_string(), missing });
+    }
+    Ok(rdr)
+}
+
+/// Consumes a leading UTF-8 BOM so the first header reads `Region`, not `\u{feff}Region`.
+pub fn skip_utf8_bom<R: BufRead>(mut input: R) -> io::Result<R> {
+    if input.fill_buf()?.starts_with(UTF8_BOM) {
+        input.consume(UTF8_BOM.len());
+    }
+    Ok(input)
+}
+
+/// Reads all rows from the CSV into a vector of `RawRecord` structs.
+pub fn read_csv(file_path: &Path) -> Result<Vec<RawRecord>, PipelineError> {
+    let mut rdr = open_csv_reader(file_path)?;
+    let mut results = Vec::new();
+    let mut progress = Progress::new(&format!("Reading {}", file_path.display()));
+    for result in rdr.deserialize() {
+        let record: RawRecord = result.map_err(|e| PipelineError::from(e).in_file(file_path))?;
+        results.push(record);
+        progress.tick();
+    }
+    progress.finish();
+    Ok(results)
+}
+
+/// Lists every `.csv` (or gzipped `.csv.gz`) file inside the given data directory, sorted by name.
+pub fn find_all_csv_files(data_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let is_csv = |path: &Path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let mut files: Vec<PathBuf> = fs::read_dir(data_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            // A `.csv.gz` only counts when there is no plain copy of the same export.
+            path.is_file()
+                && (is_csv(path)
+                    || (is_gzipped(path)
+                        && is_csv(&path.with_extension(""))
+                        && !path.with_extension("").exists()))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Runs `job` on a background thread and waits at most `timeout` for it, so a read from an
+/// unreachable network drive cannot hang the program. With no timeout the job runs inline.
+/// This is best effort: blocked file I/O cannot be cancelled on every platform, so after a
+/// `Timeout` error the thread may linger until the read returns, and its result is dropped.
+pub fn run_with_timeout<T, F>(timeout: Option<Duration>, job: F) -> Result<T, PipelineError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, PipelineError> + Send + 'static,
+{
+    let Some(limit) = timeout else {
+        return job();
+    };
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // The receiver is gone once the wait has timed out; nobody needs the result then.
+        let _ = sender.send(job());
+    });
+    match receiver.recv_timeout(limit) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Err(PipelineError::Timeout(limit)),
+        Err(RecvTimeoutError::Disconnected) => Err(io::Error::other("the file reader thread panicked").into()),
+    }
+}
+
+/// Expands `--input` values into the CSV files to load, in the order given.
+/// Each value may be a file, a directory (every CSV inside it), or a glob
+/// pattern such as `data/*.csv`. Files named more than once are read once.
+pub fn resolve_input_files(inputs: &[String]) -> Result<Vec<PathBuf>, PipelineError> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for input in inputs {
+        let path = Path::new(input);
+        let matches = if path.is_dir() {
+            find_all_csv_files(path)?
+        } else if input.contains(['*', '?', '[']) {
+            let pattern = glob::glob(input).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            pattern.filter_map(Result::ok).filter(|p| p.is_file()).collect()
+        } else if path.is_file() {
+            vec![path.to_path_buf()]
+        } else {
+            Vec::new()
+        };
+        if matches.is_empty() {
+            return Err(PipelineError::InputNotFound { pattern: input.clone() });
+        }
+        for file in matches {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Reads several CSV files and concatenates their rows into one vector.
+/// Every file must carry the `RawRecord` headers (column order may differ), so
+/// their rows line up. Each record remembers the `source_name` of the file it came from.
+pub fn read_and_merge_csvs(paths: &[PathBuf]) -> Result<Vec<RawRecord>, PipelineError> {
+    let mut merged = Vec::new();
+    for path in paths {
+        let mut records = read_csv(path)?;
+        info!("Reading file: {} ({} rows)", path.display(), records.len());
+        let source = source_name(path);
+        for record in &mut records {
+            record.source_file = source.clone();
+        }
+        merged.extend(records);
+    }
+    Ok(merged)
+}
+
+/// The name records read from `path` carry in `source_file`: the path relative to the
+/// current directory when it lies inside it, otherwise the path as given. Same-named
+/// files in different folders (`q2/data.csv`, `q4/data.csv`) therefore stay apart.
+pub fn source_name(path: &Path) -> String {
+    let relative = env::current_dir().ok().and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf));
+    relative.as_deref().unwrap_or(path).to_string_lossy().to_string()
+}
+
+/// Number of merged records that came from each of `paths`, in `paths` order.
+pub fn rows_per_file(records: &[RawRecord], paths: &[PathBuf]) -> Vec<usize> {
+    paths
+        .iter()
+        .map(|path| {
+            let source = source_name(path);
+            records.iter().filter(|r| r.source_file == source).count()
+        })
+        .collect()
+}
+
+// What `stream_csv_files` returns: processed records, validation problems, and rows read per file.
+pub type StreamedCsv = (Vec<ProcessedRecord>, ValidationLog, Vec<usize>);
+
+/// Reads CSV files one row at a time, validating, cleaning, and deriving fields as it goes.
+/// Only the processed records are kept, so memory stays bounded by the clean dataset.
+/// Returns the processed records, validation errors and warnings, and the raw row count of each file.
+pub fn stream_csv_files(
+    paths: &[PathBuf],
+    rules: &ValidationConfig,
+) -> Result<StreamedCsv, PipelineError> {
+    let mut processed = Vec::new();
+    let mut log = ValidationLog::default();
+    let mut file_rows = Vec::new();
+    for path in paths {
+        let source = source_name(path);
+        let mut rdr = open_csv_reader(path)?;
+        let mut rows = 0;
+        let mut progress = Progress::new(&format!("Streaming {}", path.display()));
+        for result in rdr.deserialize() {
+            progress.tick();
+            let mut record: RawRecord = result.map_err(|e| PipelineError::from(e).in_file(path))?;
+            record.source_file = source.clone();
+            rows += 1;
+            if let Some(clean) = clean_raw_record(&record, rows, rules, &mut log) {
+                processed.push(add_derived_fields(clean));
+            }
+        }
+        progress.finish();
+        info!("Streaming file: {} ({} rows)", path.display(), rows);
+        file_rows.push(rows);
+    }
+    Ok((processed, log, file_rows))
+}
+
+/// Writes report data to a delimited file, including headers and escaped values.
+/// With `excel_compat` the file starts with a UTF-8 BOM and uses CRLF line endings.
+pub fn write_csv(
+    file_path: &PathBuf,
+    data: &[ReportRow],
+    headers: &[&str],
+    delimiter: Delimiter,
+    excel_compat: bool,
+) -> Result<(), PipelineError> {
+    ensure_dir(file_path)?;
+    write_csv_to_writer(fs::File::create(file_path)?, data, headers, delimiter, excel_compat)
+}
+
+/// Writes report data as delimited text to any writer, such as a file or an in-memory buffer.
+pub fn write_csv_to_writer<W: Write>(
+    writer: W,
+    data: &[ReportRow],
+    headers: &[&str],
+    delimiter: Delimiter,
+    excel_compat: bool,
+) -> Result<(), PipelineError> {
+    let rows = data
+        .iter()
+        .map(|row| headers.iter().map(|&h| row.get(h).cloned().unwrap_or_default()).collect());
+    write_rows_to_writer(writer, headers, rows, delimiter, excel_compat)
+}
+
+/// Writes already formatted rows, each listing its cells in `headers` order, to a delimited file.
+pub fn write_rows<I>(
+    file_path: &PathBuf,
+    headers: &[&str],
+    rows: I,
+    delimiter: Delimiter,
+    excel_compat: bool,
+) -> Result<(), PipelineError>
+where
+    I: IntoIterator<Item = Vec<String>>,
+{
+    ensure_dir(file_path)?;
+    write_rows_to_writer(fs::File::create(file_path)?, headers, rows, delimiter, excel_compat)
+}
+
+/// Writes a header row and then the formatted rows to any writer.
+/// Cells containing the delimiter, quotes or line breaks are quoted.
+pub fn write_rows_to_writer<W, I>(
+    mut writer: W,
+    headers: &[&str],
+    rows: I,
+    delimiter: Delimiter,
+    excel_compat: bool,
+) -> Result<(), PipelineError>
+where
+    W: Write,
+    I: IntoIterator<Item = Vec<String>>,
+{
+    let mut builder = WriterBuilder::new();
+    builder.delimiter(delimiter.as_byte()).quote_style(csv::QuoteStyle::Necessary);
+    if excel_compat {
+        writer.write_all(UTF8_BOM)?;
+        builder.terminator(csv::Terminator::CRLF);
+    }
+    let mut wtr = builder.from_writer(writer);
+    wtr.write_record(headers)?;
+    for row in rows {
+        wtr.write_record(&row)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Writes report data as tab-separated values.
+pub fn write_tsv(file_path: &PathBuf, data: &[ReportRow], headers: &[&str], excel_compat: bool) -> Result<(), PipelineError> {
+    write_csv(file_path, data, headers, Delimiter::Tab, excel_compat)
+}
+
+/// Writes JSON data (pretty-formatted) to a file.
+pub fn write_json(file_path: &PathBuf, data: &JsonValue) -> Result<(), PipelineError> {
+    ensure_dir(file_path)?;
+    write_json_to_writer(io::BufWriter::new(fs::File::create(file_path)?), data)
+}
+
+/// Writes JSON data (pretty-formatted) to any writer.
+pub fn write_json_to_writer<W: Write>(mut writer: W, data: &JsonValue) -> Result<(), PipelineError> {
+    serde_json::to_writer_pretty(&mut writer, data).map_err(io::Error::from)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes processed records to a file as one pretty-printed JSON array.
+pub fn write_processed_json(file_path: &PathBuf, records: &[ProcessedRecord]) -> Result<(), PipelineError> {
+    ensure_dir(file_path)?;
+    let json_str = serde_json::to_string_pretty(records).map_err(io::Error::from)?;
+    fs::write(file_path, json_str)?;
+    Ok(())
+}
+
+/// Writes cleaned records (before derived fields are added) as one pretty-printed JSON array.
+pub fn write_cleaned_json(file_path: &PathBuf, records: &[CleanedRecord]) -> Result<(), PipelineError> {
+    ensure_dir(file_path)?;
+    let json_str = serde_json::to_string_pretty(records).map_err(io::Error::from)?;
+    fs::write(file_path, json_str)?;
+    Ok(())
+}
+
+/// Escapes text so it can be placed inside HTML markup.
+pub fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Writes report rows (cells in `headers` order) as a self-contained HTML page with an inline-styled table.
+/// Columns whose non-empty values all parse as numbers are right-aligned.
+pub fn write_html_report(file_path: &PathBuf, data: &[Vec<String>], headers: &[&str], title: &str) -> Result<(), PipelineError> {
+    ensure_dir(file_path)?;
+
+    // Detect numeric columns by trying to parse every value as f64.
+    let numeric: Vec<bool> = (0..headers.len()).map(|c| {
+        let mut values = data
+            .iter()
+            .filter_map(|row| row.get(c))
+            .filter(|v| !v.trim().is_empty() && v.as_str() != "-")
+            .peekable();
+        values.peek().is_some() && values.all(|v| v.trim().parse::<f64>().is_ok())
+    }).collect();
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", html_escape(title)));
+    html.push_str("<style>\n");
+    html.push_str("body { font-family: Arial, Helvetica, sans-serif; margin: 24px; color: #222; }\n");
+    html.push_str("h1 { font-size: 20px; }\n");
+    html.push_str("table { border-collapse: collapse; width: 100%; font-size: 13px; }\n");
+    html.push_str("th, td { border: 1px solid #ccc; padding: 6px 10px; text-align: left; }\n");
+    html.push_str("th { position: sticky; top: 0; background: #2e7d32; color: #fff; }\n");
+    html.push_str("tbody tr:nth-child(even) { background: #f2f2f2; }\n");
+    html.push_str("td.num { text-align: right; font-variant-numeric: tabular-nums; }\n");
+    html.push_str("</style>\n</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n<table>\n<thead>\n<tr>", html_escape(title)));
+    for h in headers {
+        html.push_str(&format!("<th>{}</th>", html_escape(h)));
+    }
+    html.push_str("</tr>\n</thead>\n<tbody>\n");
+    for row in data {
+        html.push_str("<tr>");
+        for (i, value) in row.iter().enumerate() {
+            let class = if numeric[i] { " class=\"num\"" } else { "" };
+            html.push_str(&format!("<td{}>{}</td>", class, html_escape(value)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n</body>\n</html>\n");
+
+    fs::write(file_path, html)?;
+    Ok(())
+}
+
+/// Exports processed records into a `flood_control_projects` SQLite table.
+/// The table is recreated on every export and all rows are inserted in one transaction.
+/// Returns the number of rows inserted.
+pub fn export_to_sqlite(records: &[ProcessedRecord], db_path: &PathBuf) -> Result<usize, Box<dyn std::error::Error>> {
+    ensure_dir(db_path)?;
+    let mut conn = Connection::open(db_path)?;
+    let tx = conn.transaction()?;
+    tx.execute_batch(
+        "DROP TABLE IF EXISTS flood_control_projects;
+         CREATE TABLE flood_control_projects (
+             region TEXT NOT NULL,
+             main_island TEXT NOT NULL,
+             funding_year INTEGER NOT NULL,
+             approved_budget_for_contract REAL NOT NULL,
+             contract_cost REAL NOT NULL,
+             start_date TEXT,
+             actual_completion_date TEXT,
+             project_latitude REAL,
+             project_longitude REAL,
+             province TEXT NOT NULL,
+             contractor TEXT NOT NULL,
+             type_of_work TEXT NOT NULL,
+             cost_savings REAL NOT NULL,
+             completion_delay_days INTEGER,
+             coordinates_imputed INTEGER NOT NULL,
+             source_file TEXT NOT NULL
+         );",
+    )?;
+
+    let mut inserted = 0;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO flood_control_projects VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        )?;
+        for r in records {
+            inserted += stmt.execute(params![
+                r.region,
+                r.main_island,
+                r.funding_year,
+                r.approved_budget_for_contract,
+                r.contract_cost,
+                r.start_date.map(|d| d.format("%Y-%m-%d").to_string()),
+                r.actual_completion_date.map(|d| d.format("%Y-%m-%d").to_string()),
+                r.project_latitude,
+                r.project_longitude,
+                r.province,
+                r.contractor,
+                r.type_of_work,
+                r.cost_savings,
+                r.completion_delay_days,
+                r.coordinates_imputed,
+                r.source_file,
+            ])?;
+        }
+    }
+    tx.commit()?;
+    Ok(inserted)
+}
+
+/// Prompt user for input
+/// Fails with `UnexpectedEof` once stdin is closed, so callers cannot loop on empty answers.
+pub fn ask_question(prompt: &str) -> io::Result<String> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+    let stdin = io::stdin();
+    let mut input = String::new();
+    if stdin.lock().read_line(&mut input)? == 0 {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "end of input"));
+    }
+    Ok(input.trim().to_string())
+}
@@ -0,0 +1,19 @@
+// ********************
+// Last names: Campo, Hallare, Lobo, Rebollos
+// Language: Rust
+// Paradigm(s): Imperative, Functional
+// ********************
+
+//! Cleaning, analysis and report generation for the DPWH flood control dataset.
+
+pub mod error;
+pub mod model;
+pub mod config;
+pub mod io;
+pub mod validate;
+pub mod transform;
+pub mod compute;
+pub mod reports;
+pub mod cache;
+pub mod manifest;
+pub mod app;
@@ -0,0 +1,154 @@
+//! Checksummed list of the files a run wrote to its output directory, so archived reports can be checked for changes.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use csv::ReaderBuilder;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::PipelineError;
+
+// ============================================================================
+// OUTPUT MANIFEST
+// ============================================================================
+
+// Name of the manifest inside the output directory; it never lists itself.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+// One file listed in the manifest.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ManifestEntry {
+    // Name relative to the output directory.
+    pub file: String,
+    pub bytes: u64,
+    // Lowercase hex SHA-256 of the file's contents.
+    pub sha256: String,
+    // Data rows below the header of a CSV/TSV, or elements of a JSON array; None for other files.
+    pub rows: Option<usize>,
+    // When the file was last written, from its modification time.
+    pub generated_at: String,
+}
+
+// Contents of `manifest.json`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Manifest {
+    pub generated_at: String,
+    pub files: Vec<ManifestEntry>,
+}
+
+// Why a listed file no longer matches its manifest entry.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ManifestProblem {
+    Missing,
+    SizeChanged { expected: u64, actual: u64 },
+    HashChanged,
+}
+
+impl fmt::Display for ManifestProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ManifestProblem::Missing => write!(f, "missing"),
+            ManifestProblem::SizeChanged { expected, actual } => {
+                write!(f, "size changed ({} bytes, expected {})", actual, expected)
+            }
+            ManifestProblem::HashChanged => write!(f, "contents changed (SHA-256 differs)"),
+        }
+    }
+}
+
+/// SHA-256 (lowercase hex) and size of a file, read in chunks rather than all at once.
+pub fn hash_file(path: &Path) -> io::Result<(String, u64)> {
+    let mut hasher = Sha256::new();
+    let bytes = io::copy(&mut BufReader::new(fs::File::open(path)?), &mut hasher)?;
+    Ok((format!("{:x}", hasher.finalize()), bytes))
+}
+
+/// Counts the data rows of a CSV/TSV report or the elements of a JSON array.
+/// Other files, and files that fail to parse, have no row count.
+pub fn count_rows(path: &Path) -> Option<usize> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    match extension.as_str() {
+        "csv" | "tsv" => {
+            let delimiter = if extension == "tsv" { b'\t' } else { b',' };
+            let mut rdr = ReaderBuilder::new().delimiter(delimiter).flexible(true).from_path(path).ok()?;
+            rdr.records().try_fold(0, |rows, record| record.map(|_| rows + 1)).ok()
+        }
+        "json" => {
+            let reader = BufReader::new(fs::File::open(path).ok()?);
+            serde_json::from_reader::<_, Vec<IgnoredAny>>(reader).ok().map(|items| items.len())
+        }
+        _ => None,
+    }
+}
+
+/// Hashes the given files, all inside `dir`, into a manifest sorted by name.
+/// The manifest itself is never listed, and a path listed twice appears once.
+pub fn build_manifest(dir: &Path, files: &[PathBuf]) -> io::Result<Manifest> {
+    let mut names: Vec<String> = files
+        .iter()
+        .filter_map(|path| path.file_name().map(|n| n.to_string_lossy().to_string()))
+        .filter(|name| name != MANIFEST_FILE)
+        .collect();
+    names.sort();
+    names.dedup();
+
+    let mut entries = Vec::new();
+    for name in names {
+        let path = dir.join(&name);
+        let (sha256, bytes) = hash_file(&path)?;
+        let modified: DateTime<Local> = fs::metadata(&path)?.modified()?.into();
+        entries.push(ManifestEntry {
+            rows: count_rows(&path),
+            file: name,
+            bytes,
+            sha256,
+            generated_at: modified.to_rfc3339(),
+        });
+    }
+    Ok(Manifest { generated_at: Local::now().to_rfc3339(), files: entries })
+}
+
+/// Writes `manifest.json` for the files a run wrote into `dir` and returns its path.
+/// Files left in `dir` by earlier runs are not listed.
+pub fn write_manifest(dir: &Path, files: &[PathBuf]) -> Result<PathBuf, PipelineError> {
+    let manifest = build_manifest(dir, files)?;
+    let path = dir.join(MANIFEST_FILE);
+    let text = serde_json::to_string_pretty(&manifest).map_err(io::Error::from)?;
+    fs::write(&path, text)?;
+    Ok(path)
+}
+
+/// Reads the manifest in `dir`.
+pub fn read_manifest(dir: &Path) -> Result<Manifest, PipelineError> {
+    let path = dir.join(MANIFEST_FILE);
+    let text = fs::read_to_string(&path)
+        .map_err(|e| io::Error::new(e.kind(), format!("cannot read {}: {}", path.display(), e)))?;
+    let manifest = serde_json::from_str(&text)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a valid manifest: {}", path.display(), e)))?;
+    Ok(manifest)
+}
+
+/// Re-hashes the files `manifest` lists in `dir`.
+/// Returns each listed file that is missing or no longer matches, in manifest order.
+pub fn verify_manifest(dir: &Path, manifest: &Manifest) -> io::Result<Vec<(String, ManifestProblem)>> {
+    let mut problems = Vec::new();
+    for entry in &manifest.files {
+        let path = dir.join(&entry.file);
+        if !path.is_file() {
+            problems.push((entry.file.clone(), ManifestProblem::Missing));
+            continue;
+        }
+        let (sha256, bytes) = hash_file(&path)?;
+        if bytes != entry.bytes {
+            problems.push((entry.file.clone(), ManifestProblem::SizeChanged { expected: entry.bytes, actual: bytes }));
+        } else if sha256 != entry.sha256 {
+            problems.push((entry.file.clone(), ManifestProblem::HashChanged));
+        }
+    }
+    Ok(problems)
+}
@@ -0,0 +1,329 @@
+//! Record types shared by every stage of the pipeline.
+
+use std::collections::HashMap;
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// DATA MODEL
+// ============================================================================
+
+// Represents one raw CSV record directly from the dataset.
+// Fields correspond to CSV headers.
+#[derive(Deserialize, Clone)]
+pub struct RawRecord {
+    #[serde(rename = "Region")]
+    pub region: String,
+    #[serde(rename = "MainIsland")]
+    pub main_island: String,
+    #[serde(rename = "FundingYear")]
+    pub funding_year: String,
+    #[serde(rename = "ApprovedBudgetForContract")]
+    pub approved_budget_for_contract: String,
+    #[serde(rename = "ContractCost")]
+    pub contract_cost: String,
+    #[serde(rename = "StartDate")]
+    pub start_date: String,
+    #[serde(rename = "ActualCompletionDate")]
+    pub actual_completion_date: String,
+    #[serde(rename = "ProjectLatitude")]
+    pub project_latitude: String,
+    #[serde(rename = "ProjectLongitude")]
+    pub project_longitude: String,
+    #[serde(rename = "Province")]
+    pub province: String,
+    #[serde(rename = "Contractor")]
+    pub contractor: String,
+    #[serde(rename = "TypeOfWork")]
+    pub type_of_work: String,
+    // Name of the CSV file the record was read from (not a CSV column).
+    #[serde(skip)]
+    pub source_file: String,
+}
+
+impl RawRecord {
+    // CSV columns every input file must have, in dataset order.
+    pub const HEADERS: [&str; 12] = [
+        "Region", "MainIsland", "FundingYear", "ApprovedBudgetForContract", "ContractCost", "StartDate",
+        "ActualCompletionDate", "ProjectLatitude", "ProjectLongitude", "Province", "Contractor", "TypeOfWork",
+    ];
+
+    /// Looks up a field by its CSV header name.
+    pub fn field(&self, header: &str) -> Option<&str> {
+        let value = match header {
+            "Region" => &self.region,
+            "MainIsland" => &self.main_island,
+            "FundingYear" => &self.funding_year,
+            "ApprovedBudgetForContract" => &self.approved_budget_for_contract,
+            "ContractCost" => &self.contract_cost,
+            "StartDate" => &self.start_date,
+            "ActualCompletionDate" => &self.actual_completion_date,
+            "ProjectLatitude" => &self.project_latitude,
+            "ProjectLongitude" => &self.project_longitude,
+            "Province" => &self.province,
+            "Contractor" => &self.contractor,
+            "TypeOfWork" => &self.type_of_work,
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+// Represents a cleaned record where fields are converted to proper data types.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct CleanedRecord {
+    pub region: String,
+    pub main_island: String,
+    pub funding_year: i32,
+    pub approved_budget_for_contract: f64,
+    pub contract_cost: f64,
+    #[serde(with = "iso_date")]
+    pub start_date: Option<NaiveDate>,
+    #[serde(with = "iso_date")]
+    pub actual_completion_date: Option<NaiveDate>,
+    pub project_latitude: Option<f64>,
+    pub project_longitude: Option<f64>,
+    pub province: String,
+    pub contractor: String,
+    pub type_of_work: String,
+    // Name of the CSV file the record was read from.
+    #[serde(default)]
+    pub source_file: String,
+}
+
+// Medians of the optional fields, taken over records that have all of them
+// (both coordinates and both dates); None when there are no such records.
+#[derive(Serialize, Clone, PartialEq, Debug, Default)]
+pub struct DatasetMedians {
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    // Completion delay: days from start to actual completion.
+    pub delay_days: Option<f64>,
+    // Number of complete records the medians were taken from.
+    pub complete_records: usize,
+}
+
+impl CleanedRecord {
+    /// Fills the optional fields this record is missing from the dataset medians:
+    /// coordinates with the median latitude/longitude, and one missing date with the other
+    /// date plus or minus the median delay. A record with neither date keeps both empty.
+    pub fn with_defaults(mut self, medians: &DatasetMedians) -> CleanedRecord {
+        self.project_latitude = self.project_latitude.or(medians.latitude);
+        self.project_longitude = self.project_longitude.or(medians.longitude);
+        if let Some(days) = medians.delay_days {
+            let delay = chrono::Duration::days(days.round() as i64);
+            match (self.start_date, self.actual_completion_date) {
+                (Some(start), None) => self.actual_completion_date = Some(start + delay),
+                (None, Some(completion)) => self.start_date = Some(completion - delay),
+                _ => {}
+            }
+        }
+        self
+    }
+}
+
+// Represents a fully processed record with computed derived metrics.
+// Serializes with snake_case field names, dates as "YYYY-MM-DD" and missing values as null.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ProcessedRecord {
+    pub region: String,
+    pub main_island: String,
+    pub funding_year: i32,
+    pub approved_budget_for_contract: f64,
+    pub contract_cost: f64,
+    #[serde(with = "iso_date")]
+    pub start_date: Option<NaiveDate>,
+    #[serde(with = "iso_date")]
+    pub actual_completion_date: Option<NaiveDate>,
+    pub project_latitude: Option<f64>,
+    pub project_longitude: Option<f64>,
+    pub province: String,
+    pub contractor: String,
+    pub type_of_work: String,
+    pub cost_savings: f64,
+    pub completion_delay_days: Option<i64>,
+    // Whether a missing coordinate was filled in with the province average.
+    pub coordinates_imputed: bool,
+    // Name of the CSV file the record was read from.
+    #[serde(default)]
+    pub source_file: String,
+}
+
+// Serde helpers for optional dates: ISO-8601 "YYYY-MM-DD" strings, or null when missing.
+mod iso_date {
+    use chrono::NaiveDate;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    const FORMAT: &str = "%Y-%m-%d";
+
+    pub fn serialize<S: Serializer>(date: &Option<NaiveDate>, serializer: S) -> Result<S::Ok, S::Error> {
+        match date {
+            Some(date) => serializer.serialize_some(&date.format(FORMAT).to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NaiveDate>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| NaiveDate::parse_from_str(&text, FORMAT).map_err(de::Error::custom))
+            .transpose()
+    }
+}
+
+// How far a project's contract cost went over its approved budget:
+// Minor is up to 5% over, Moderate up to 20%, Severe beyond that.
+// Variants are ordered from least to most severe.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub enum OverrunSeverity {
+    None,
+    Minor,
+    Moderate,
+    Severe,
+}
+
+impl OverrunSeverity {
+    // Every level, least severe first.
+    pub const ALL: [OverrunSeverity; 4] = [
+        OverrunSeverity::None,
+        OverrunSeverity::Minor,
+        OverrunSeverity::Moderate,
+        OverrunSeverity::Severe,
+    ];
+
+    /// Name used in report cells and summary keys.
+    pub fn label(self) -> &'static str {
+        match self {
+            OverrunSeverity::None => "None",
+            OverrunSeverity::Minor => "Minor",
+            OverrunSeverity::Moderate => "Moderate",
+            OverrunSeverity::Severe => "Severe",
+        }
+    }
+}
+
+// Generic row structure for writing reports.
+// Each key-value represents one cell of data.
+pub type ReportRow = HashMap<String, String>;
+
+// A typed report row that lists its columns and formats its own cells.
+// The CSV/TSV, HTML and console outputs use `cells`; the JSON export serializes the struct.
+pub trait ReportRecord: Serialize {
+    // Column headers, in output order.
+    const HEADERS: &'static [&'static str];
+
+    /// Formatted cell values, one per entry of `HEADERS` and in the same order.
+    fn cells(&self) -> Vec<String>;
+
+    /// The formatted value of one column, or `None` when the report has no such header.
+    fn cell(&self, header: &str) -> Option<String> {
+        let index = Self::HEADERS.iter().position(|&h| h == header)?;
+        self.cells().into_iter().nth(index)
+    }
+
+    /// Unformatted values to sort on, one per entry of `HEADERS` and in the same order.
+    fn sort_values(&self) -> Vec<SortValue>;
+}
+
+// The raw value of one report cell, used to sort rows without parsing formatted text.
+#[derive(Clone, PartialEq, Debug)]
+pub enum SortValue {
+    Number(f64),
+    Text(String),
+    // No value, e.g. an N/A cell; sorts after everything else.
+    Missing,
+}
+
+// Field separator used when writing delimited report files.
+#[derive(Clone, Copy)]
+pub enum Delimiter {
+    Comma,
+    Tab,
+}
+
+impl Delimiter {
+    /// Returns the byte handed to the CSV writer.
+    pub fn as_byte(self) -> u8 {
+        match self {
+            Delimiter::Comma => b',',
+            Delimiter::Tab => b'\t',
+        }
+    }
+}
+
+// Which data files a report is written as; the HTML page is written either way.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OutputFormat {
+    // CSV plus its tab-separated copy.
+    Csv,
+    // JSON array of the report records.
+    Json,
+    Both,
+}
+
+impl OutputFormat {
+    /// Parses `csv`, `json` or `both` (any case).
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "csv" => Some(OutputFormat::Csv),
+            "json" => Some(OutputFormat::Json),
+            "both" => Some(OutputFormat::Both),
+            _ => None,
+        }
+    }
+
+    /// Whether the CSV and TSV files are written.
+    pub fn writes_csv(self) -> bool {
+        self != OutputFormat::Json
+    }
+
+    /// Whether the JSON file is written.
+    pub fn writes_json(self) -> bool {
+        self != OutputFormat::Csv
+    }
+}
+
+// One exact (latitude, longitude) pair listed under more than one province,
+// which points at a geocoding error in the source data.
+pub struct CoordinateConflict {
+    pub latitude: f64,
+    pub longitude: f64,
+    // Each province using the coordinate and how many records it has there, most records first.
+    pub provinces: Vec<(String, usize)>,
+}
+
+// Used to hold results of record validation.
+// Warnings flag suspicious values without rejecting the record.
+pub struct ValidationResult {
+    pub is_valid: bool,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// A kept record whose MainIsland disagrees with the island group its Region belongs to.
+pub struct IslandWarning {
+    pub source_file: String,
+    // 1-based line in the source file, counting the header.
+    pub row: usize,
+    pub region: String,
+    pub main_island: String,
+    pub expected_island: String,
+}
+
+// Problems found while cleaning rows: messages for rejected rows and island mismatches in kept ones.
+#[derive(Default)]
+pub struct ValidationLog {
+    pub errors: Vec<String>,
+    pub island_warnings: Vec<IslandWarning>,
+}
+
+// "Valid", or one indented bullet line per error.
+impl std::fmt::Display for ValidationResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.is_valid {
+            return write!(f, "Valid");
+        }
+        let bullets: Vec<String> = self.errors.iter().map(|e| format!("  - {}", e)).collect();
+        write!(f, "{}", bullets.join("\n"))
+    }
+}
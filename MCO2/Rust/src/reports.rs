@@ -0,0 +1,1305 @@
+//! Report generators, the summary JSON and the console report writer.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use log::info;
+use serde::Serialize;
+use serde_json::{json, Value as JsonValue};
+use prettytable::{Table, Row, Cell, format};
+
+use crate::error::PipelineError;
+use crate::model::{ProcessedRecord, OverrunSeverity, ReportRecord, SortValue, Delimiter};
+use crate::config::{OutputSettings, PreviewConfig, ReportSort};
+use crate::io::{ensure_dir, write_rows_to_writer, write_json, write_html_report};
+use crate::validate::validate_number;
+use crate::transform::{compute_overrun_severity, detect_severe_delays, estimate_completion_date, extract_quarter, has_negative_delay};
+use crate::compute::{
+    format_number, format_large_number, calculate_median, calculate_percentile, calculate_average,
+    calculate_std_dev, calculate_coefficient_of_variation, calculate_moving_average, calculate_gini, compute_budget_weighted_delay, calculate_cumulative_budget,
+    calculate_cumulative_savings, calculate_average_i64, calculate_percentage, calculate_reliability_index, compute_regional_budget_cagr, group_by, sort_by_multiple_keys,
+    OrdF64,
+};
+
+// ============================================================================
+// REPORT GENERATION - REPORT 1: REGIONAL EFFICIENCY
+// ============================================================================
+
+// Label width and longest bar (in characters) of the console bar charts.
+pub const BAR_LABEL_WIDTH: usize = 30;
+pub const BAR_MAX_WIDTH: usize = 50;
+
+/// One row of Report 1: a region's budget, savings and delay statistics.
+/// Serialized with raw numbers for the JSON export; `cells` formats it for the other outputs.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct Report1Temp {
+    pub rank: usize,
+    pub region: String,
+    pub main_island: String,
+    pub total_budget: f64,
+    pub min_contract_cost: f64,
+    pub max_contract_cost: f64,
+    pub median_savings: f64,
+    pub p25_savings: f64,
+    pub p75_savings: f64,
+    pub avg_delay: f64,
+    pub delay_std_dev: f64,
+    pub p90_delay: f64,
+    pub high_delay_pct: f64,
+    pub efficiency_score: f64,
+    // Yearly growth of the region's total budget, in percent; None when it was funded in one year only.
+    pub region_cagr: Option<f64>,
+    // Decimal places for `TotalBudget` in the formatted cells.
+    #[serde(skip)]
+    pub budget_decimals: usize,
+}
+
+impl ReportRecord for Report1Temp {
+    const HEADERS: &'static [&'static str] = &[
+        "Rank", "Region", "MainIsland", "TotalBudget", "MinContractCost", "MaxContractCost", "MedianSavings",
+        "P25Savings", "P75Savings", "AvgDelay", "DelayStdDev", "P90Delay", "HighDelayPct", "EfficiencyScore", "RegionCagr",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.rank.to_string(),
+            self.region.clone(),
+            self.main_island.clone(),
+            format_number(self.total_budget, self.budget_decimals),
+            format_large_number(self.min_contract_cost),
+            format_large_number(self.max_contract_cost),
+            format_number(self.median_savings, 2),
+            format_number(self.p25_savings, 2),
+            format_number(self.p75_savings, 2),
+            format_number(self.avg_delay, 2),
+            format_number(self.delay_std_dev, 2),
+            format_number(self.p90_delay, 2),
+            format_number(self.high_delay_pct, 2),
+            format_number(self.efficiency_score, 2),
+            self.region_cagr.map_or("N/A".to_string(), |cagr| format_number(cagr, 2)),
+        ]
+    }
+
+    fn sort_values(&self) -> Vec<SortValue> {
+        vec![
+            SortValue::Number(self.rank as f64),
+            SortValue::Text(self.region.clone()),
+            SortValue::Text(self.main_island.clone()),
+            SortValue::Number(self.total_budget),
+            SortValue::Number(self.min_contract_cost),
+            SortValue::Number(self.max_contract_cost),
+            SortValue::Number(self.median_savings),
+            SortValue::Number(self.p25_savings),
+            SortValue::Number(self.p75_savings),
+            SortValue::Number(self.avg_delay),
+            SortValue::Number(self.delay_std_dev),
+            SortValue::Number(self.p90_delay),
+            SortValue::Number(self.high_delay_pct),
+            SortValue::Number(self.efficiency_score),
+            self.region_cagr.map_or(SortValue::Missing, SortValue::Number),
+        ]
+    }
+}
+
+/// Generate Report 1: Regional Flood Mitigation Efficiency Summary
+/// `TotalBudget` is shown with `budget_decimals` decimal places.
+pub fn generate_report1(records: &[ProcessedRecord], budget_decimals: usize) -> Vec<Report1Temp> {
+    // Group projects by region
+    let grouped = group_by(records, |r| r.region.clone());
+
+    // Temporary storage for per-region stats
+    let mut temp: Vec<Report1Temp> = Vec::new();
+    let budget_growth = compute_regional_budget_cagr(records);
+
+    // Compute aggregated metrics for each region
+    for (region, recs) in grouped {
+        if recs.is_empty() { continue; }
+        
+        let main_island = recs[0].main_island.clone();
+        let total_budget: f64 = recs.iter().map(|r| r.approved_budget_for_contract).sum();
+        let min_contract_cost = recs.iter().map(|r| r.contract_cost).fold(f64::INFINITY, f64::min);
+        let max_contract_cost = recs.iter().map(|r| r.contract_cost).fold(f64::NEG_INFINITY, f64::max);
+        
+        let savings: Vec<f64> = recs.iter().map(|r| r.cost_savings).collect();
+        let median_savings = calculate_median(&savings);
+        let p25_savings = calculate_percentile(&savings, 25.0);
+        let p75_savings = calculate_percentile(&savings, 75.0);
+        
+        let delays: Vec<i64> = recs.iter().filter_map(|r| r.completion_delay_days).collect();
+        let avg_delay = calculate_average_i64(&delays);
+        let delays_f64: Vec<f64> = delays.iter().map(|&d| d as f64).collect();
+        let delay_std_dev = calculate_std_dev(&delays_f64);
+        let p90_delay = calculate_percentile(&delays_f64, 90.0);
+
+        // % of projects delayed over 30 days
+        let high_delay_pct = if !delays.is_empty() {
+            calculate_percentage(delays.iter().filter(|&&d| d > 30).count() as f64, delays.len() as f64)
+        } else { 0.0 };
+
+        // Efficiency Score: proportional to savings but penalized by delay
+        let efficiency_score = if avg_delay > 0.0 {
+            ((median_savings / avg_delay) * 100.0).clamp(0.0, 100.0)
+        } else { 0.0 };
+
+        // Budget growth per year, in percent
+        let region_cagr = budget_growth.get(&region).map(|cagr| cagr * 100.0);
+
+        temp.push(Report1Temp { 
+            rank: 0,
+            region, 
+            main_island, 
+            total_budget, 
+            min_contract_cost, 
+            max_contract_cost, 
+            median_savings, 
+            p25_savings, 
+            p75_savings, 
+            avg_delay, 
+            delay_std_dev, 
+            p90_delay, 
+            high_delay_pct, 
+            efficiency_score,
+            region_cagr,
+            budget_decimals,
+        });
+    }
+
+    // Rank by efficiency score, highest first; ties keep region-name order
+    temp.sort_by(|a, b| a.region.cmp(&b.region));
+    temp.sort_by(|a, b| b.efficiency_score.partial_cmp(&a.efficiency_score).unwrap_or(std::cmp::Ordering::Equal));
+
+    // Number the ranks once the order is final
+    for (i, r) in temp.iter_mut().enumerate() {
+        r.rank = i + 1;
+    }
+    temp
+}
+
+// ============================================================================
+// REPORT GENERATION - REPORT 2: CONTRACTOR RANKING
+// ============================================================================
+
+/// One row of Report 2: a contractor's totals and reliability.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct Report2Temp {
+    pub rank: usize,
+    pub contractor: String,
+    pub total_cost: f64,
+    pub num_projects: usize,
+    pub avg_delay: f64,
+    pub total_savings: f64,
+    pub reliability_index: f64,
+    pub risk_flag: String,
+    // Decimal places for `TotalCost` and `TotalSavings` in the formatted cells.
+    #[serde(skip)]
+    pub budget_decimals: usize,
+}
+
+impl ReportRecord for Report2Temp {
+    const HEADERS: &'static [&'static str] = &[
+        "Rank", "Contractor", "TotalCost", "NumProjects", "AvgDelay", "TotalSavings", "ReliabilityIndex", "RiskFlag",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.rank.to_string(),
+            self.contractor.clone(),
+            format_number(self.total_cost, self.budget_decimals),
+            self.num_projects.to_string(),
+            format_number(self.avg_delay, 2),
+            format_number(self.total_savings, self.budget_decimals),
+            format_number(self.reliability_index, 2),
+            self.risk_flag.clone(),
+        ]
+    }
+
+    fn sort_values(&self) -> Vec<SortValue> {
+        vec![
+            SortValue::Number(self.rank as f64),
+            SortValue::Text(self.contractor.clone()),
+            SortValue::Number(self.total_cost),
+            SortValue::Number(self.num_projects as f64),
+            SortValue::Number(self.avg_delay),
+            SortValue::Number(self.total_savings),
+            SortValue::Number(self.reliability_index),
+            SortValue::Text(self.risk_flag.clone()),
+        ]
+    }
+}
+
+/// Qualitative label for a reliability index: below 50 is "High Risk".
+pub fn risk_flag(reliability_index: f64) -> &'static str {
+    if reliability_index < 50.0 { "High Risk" } else { "Low Risk" }
+}
+
+/// Generate Report 2: Top Contractors Performance Ranking
+/// Keeps the `top_n` contractors by total cost (0 keeps all of them).
+/// Projects with an "Unknown" contractor are excluded unless `include_unknown` is set,
+/// in which case they are ranked as one visibly labelled aggregate entry.
+/// `TotalCost` and `TotalSavings` are shown with `budget_decimals` decimal places.
+pub fn generate_report2(records: &[ProcessedRecord], top_n: usize, include_unknown: bool, budget_decimals: usize) -> Vec<Report2Temp> {
+    // Group by contractor; excluded "Unknown" projects land under `None` and are skipped.
+    let grouped = group_by(records, |r| {
+        if r.contractor != "Unknown" {
+            Some(r.contractor.clone())
+        } else if include_unknown {
+            Some("Unknown (aggregated, unreliable)".to_string())
+        } else {
+            None
+        }
+    });
+
+    let mut stats: Vec<Report2Temp> = Vec::new();
+    for (contractor, recs) in grouped {
+        let Some(contractor) = contractor else { continue };
+        if recs.len() < 5 { continue; } // ignore small sample sizes
+
+        let total_cost: f64 = recs.iter().map(|r| r.contract_cost).sum();
+        let total_savings: f64 = recs.iter().map(|r| r.cost_savings).sum();
+        let delays: Vec<i64> = recs.iter().filter_map(|r| r.completion_delay_days).collect();
+        let avg_delay = calculate_average_i64(&delays);
+
+        // Compute contractor performance (higher = better)
+        let reliability_index = calculate_reliability_index(total_cost, total_savings, avg_delay);
+
+        // Assign qualitative risk label
+        let risk_flag = risk_flag(reliability_index).to_string();
+
+        stats.push(Report2Temp { 
+            rank: 0,
+            contractor, 
+            total_cost, 
+            num_projects: recs.len(), 
+            avg_delay, 
+            total_savings, 
+            reliability_index, 
+            risk_flag,
+            budget_decimals,
+        });
+    }
+
+    // Keep only the top N by total_cost, largest first
+    let limit = if top_n > 0 { top_n } else { stats.len() };
+    let mut stats = crate::compute::top_n(stats, limit, |s| OrdF64(s.total_cost));
+
+    // Number the ranks in the kept order
+    for (i, r) in stats.iter_mut().enumerate() {
+        r.rank = i + 1;
+    }
+    stats
+}
+
+// ============================================================================
+// REPORT GENERATION - REPORT 3: COST OVERRUN TRENDS
+// ============================================================================
+
+/// One row of Report 3: savings and overruns for one type of work in one year.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct Report3Temp {
+    pub funding_year: i32,
+    pub type_of_work: String,
+    pub total_projects: usize,
+    pub avg_savings: f64,
+    pub savings_std_dev: f64,
+    pub overrun_rate: f64,
+    pub overrun_severity: OverrunSeverity,
+    #[serde(rename = "YoYChange")]
+    pub yoy_change: f64,
+    // Average of `avg_savings` over this and the preceding years of the same type of work;
+    // None until enough years are available to fill the window.
+    pub moving_avg_savings: Option<f64>,
+}
+
+impl ReportRecord for Report3Temp {
+    const HEADERS: &'static [&'static str] = &[
+        "FundingYear", "TypeOfWork", "TotalProjects", "AvgSavings", "SavingsStdDev", "OverrunRate",
+        "OverrunSeverity", "YoYChange", "MovingAvgSavings",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.funding_year.to_string(),
+            self.type_of_work.clone(),
+            self.total_projects.to_string(),
+            format_number(self.avg_savings, 2),
+            format_number(self.savings_std_dev, 2),
+            format_number(self.overrun_rate, 2),
+            self.overrun_severity.label().to_string(),
+            format_number(self.yoy_change, 2),
+            self.moving_avg_savings.map_or("N/A".to_string(), |avg| format_number(avg, 2)),
+        ]
+    }
+
+    fn sort_values(&self) -> Vec<SortValue> {
+        vec![
+            SortValue::Number(self.funding_year as f64),
+            SortValue::Text(self.type_of_work.clone()),
+            SortValue::Number(self.total_projects as f64),
+            SortValue::Number(self.avg_savings),
+            SortValue::Number(self.savings_std_dev),
+            SortValue::Number(self.overrun_rate),
+            // By severity rather than by name
+            SortValue::Number(self.overrun_severity as u8 as f64),
+            SortValue::Number(self.yoy_change),
+            self.moving_avg_savings.map_or(SortValue::Missing, SortValue::Number),
+        ]
+    }
+}
+
+/// Generate Report 3: Annual Project Type Cost Overrun Trends
+/// `MovingAvgSavings` smooths `AvgSavings` over `moving_average_years` consecutive
+/// calendar years of each type of work; a window spanning a year with no projects is `N/A`.
+pub fn generate_report3(records: &[ProcessedRecord], moving_average_years: usize) -> Vec<Report3Temp> {
+    // Group projects by year + type
+    let grouped = group_by(records, |r| (r.funding_year, r.type_of_work.clone()));
+
+    // Helper for storing YoY comparisons
+    let mut year_type_data: HashMap<String, HashMap<i32, f64>> = HashMap::new();
+    let mut temp: Vec<Report3Temp> = Vec::new();
+
+    // Compute metrics per group
+    for ((year, type_of_work), recs) in grouped {
+
+        let savings: Vec<f64> = recs.iter().map(|r| r.cost_savings).collect();
+        let avg_savings = calculate_average(&savings);
+        let savings_std_dev = calculate_std_dev(&savings);
+        let overrun_rate = if !savings.is_empty() {
+            calculate_percentage(savings.iter().filter(|&&s| s < 0.0).count() as f64, savings.len() as f64)
+        } else { 0.0 };
+
+        // Most common severity in the group; ties go to the more severe level.
+        let mut severity_counts: HashMap<OverrunSeverity, usize> = HashMap::new();
+        for r in &recs {
+            *severity_counts.entry(compute_overrun_severity(r)).or_default() += 1;
+        }
+        let overrun_severity = severity_counts
+            .into_iter()
+            .max_by_key(|&(severity, count)| (count, severity))
+            .map_or(OverrunSeverity::None, |(severity, _)| severity);
+
+        year_type_data.entry(type_of_work.clone()).or_default().insert(year, avg_savings);
+        temp.push(Report3Temp { 
+            funding_year: year, 
+            type_of_work, 
+            total_projects: recs.len(), 
+            avg_savings, 
+            savings_std_dev, 
+            overrun_rate, 
+            overrun_severity,
+            yoy_change: 0.0,
+            moving_avg_savings: None,
+        });
+    }
+
+    // Moving average of each type's yearly savings over consecutive calendar
+    // years; a year with no projects is a gap, not a neighbour of the next one
+    let mut rows_by_type: HashMap<String, BTreeMap<i32, usize>> = HashMap::new();
+    for (i, row) in temp.iter().enumerate() {
+        rows_by_type.entry(row.type_of_work.clone()).or_default().insert(row.funding_year, i);
+    }
+    for years in rows_by_type.values() {
+        let (Some((&first, _)), Some((&last, _))) = (years.first_key_value(), years.last_key_value()) else {
+            continue;
+        };
+        let yearly: Vec<Option<f64>> = (first..=last)
+            .map(|year| years.get(&year).map(|&i| temp[i].avg_savings))
+            .collect();
+        let smoothed = calculate_moving_average(&yearly, moving_average_years);
+        for (year, avg) in (first..=last).zip(smoothed) {
+            if let Some(&i) = years.get(&year) {
+                temp[i].moving_avg_savings = avg;
+            }
+        }
+    }
+
+    // Compute YoY changes relative to 2021
+    for row in &mut temp {
+        if let Some(years) = year_type_data.get(&row.type_of_work)
+            && let Some(&baseline) = years.get(&2021)
+            && row.funding_year != 2021
+            && baseline != 0.0
+        {
+            row.yoy_change = ((row.avg_savings - baseline) / baseline.abs()) * 100.0;
+        }
+    }
+
+    // Sort: oldest year first, then highest avg_savings first (within same year)
+    let keys: [fn(&Report3Temp) -> OrdF64; 2] = [
+        |r| OrdF64(f64::from(r.funding_year)),
+        |r| OrdF64(-r.avg_savings),
+    ];
+    sort_by_multiple_keys(&mut temp, &keys);
+    temp
+}
+
+// ============================================================================
+// REPORT GENERATION - REPORT 6: MAIN ISLAND SUMMARY
+// ============================================================================
+
+/// One row of Report 6: budget, cost and savings totals for one island group.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct Report6Temp {
+    pub main_island: String,
+    pub total_budget: f64,
+    pub total_contract_cost: f64,
+    pub total_savings: f64,
+    pub median_savings: f64,
+    pub num_projects: usize,
+    pub avg_delay: f64,
+    // Latest completion date of the island's projects, estimating missing ones from `avg_delay`.
+    pub estimated_completion: Option<NaiveDate>,
+    // Decimal places for the budget, cost and savings totals in the formatted cells.
+    #[serde(skip)]
+    pub budget_decimals: usize,
+}
+
+impl ReportRecord for Report6Temp {
+    const HEADERS: &'static [&'static str] = &[
+        "MainIsland", "TotalBudget", "TotalContractCost", "TotalSavings", "MedianSavings", "NumProjects", "AvgDelay",
+        "EstimatedCompletion",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.main_island.clone(),
+            format_number(self.total_budget, self.budget_decimals),
+            format_number(self.total_contract_cost, self.budget_decimals),
+            format_number(self.total_savings, self.budget_decimals),
+            format_number(self.median_savings, 2),
+            self.num_projects.to_string(),
+            format_number(self.avg_delay, 2),
+            self.estimated_completion.map(|d| d.to_string()).unwrap_or_default(),
+        ]
+    }
+
+    fn sort_values(&self) -> Vec<SortValue> {
+        vec![
+            SortValue::Text(self.main_island.clone()),
+            SortValue::Number(self.total_budget),
+            SortValue::Number(self.total_contract_cost),
+            SortValue::Number(self.total_savings),
+            SortValue::Number(self.median_savings),
+            SortValue::Number(self.num_projects as f64),
+            SortValue::Number(self.avg_delay),
+            // ISO dates sort chronologically as text
+            self.estimated_completion.map_or(SortValue::Missing, |d| SortValue::Text(d.to_string())),
+        ]
+    }
+}
+
+/// Generate Report 6: Savings Rollup by Main Island
+/// `EstimatedCompletion` fills in missing completion dates with the island's average delay.
+/// The budget, cost and savings totals are shown with `budget_decimals` decimal places.
+pub fn generate_report6(records: &[ProcessedRecord], budget_decimals: usize) -> Vec<Report6Temp> {
+    // Group projects by island group; blank islands get their own bucket
+    let grouped = group_by(records, |r| {
+        if r.main_island.trim().is_empty() {
+            "Unspecified".to_string()
+        } else {
+            r.main_island.clone()
+        }
+    });
+
+    let mut temp: Vec<Report6Temp> = grouped.into_iter().map(|(main_island, recs)| {
+        let savings: Vec<f64> = recs.iter().map(|r| r.cost_savings).collect();
+        let delays: Vec<i64> = recs.iter().filter_map(|r| r.completion_delay_days).collect();
+        let avg_delay = calculate_average_i64(&delays);
+        Report6Temp {
+            main_island,
+            total_budget: recs.iter().map(|r| r.approved_budget_for_contract).sum(),
+            total_contract_cost: recs.iter().map(|r| r.contract_cost).sum(),
+            total_savings: savings.iter().sum(),
+            median_savings: calculate_median(&savings),
+            num_projects: recs.len(),
+            avg_delay,
+            estimated_completion: recs.iter().filter_map(|r| estimate_completion_date(r, avg_delay)).max(),
+            budget_decimals,
+        }
+    }).collect();
+
+    // Sort by total budget (descending)
+    temp.sort_by(|a, b| b.total_budget.partial_cmp(&a.total_budget).unwrap_or(std::cmp::Ordering::Equal));
+    temp
+}
+
+// ============================================================================
+// REPORT GENERATION - REPORT 8: ISLAND BUDGET WATERFALL
+// ============================================================================
+
+/// One row of Report 8: budget against contract cost for one island group in one year.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct Report8Temp {
+    pub funding_year: i32,
+    pub main_island: String,
+    pub total_approved_budget: f64,
+    pub total_contract_cost: f64,
+    pub total_savings: f64,
+    pub savings_pct: f64,
+    pub num_projects: usize,
+    // Decimal places for the budget, cost and savings totals in the formatted cells.
+    #[serde(skip)]
+    pub budget_decimals: usize,
+}
+
+impl ReportRecord for Report8Temp {
+    const HEADERS: &'static [&'static str] = &[
+        "FundingYear", "MainIsland", "TotalApprovedBudget", "TotalContractCost", "TotalSavings", "SavingsPct",
+        "NumProjects",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.funding_year.to_string(),
+            self.main_island.clone(),
+            format_number(self.total_approved_budget, self.budget_decimals),
+            format_number(self.total_contract_cost, self.budget_decimals),
+            format_number(self.total_savings, self.budget_decimals),
+            format_number(self.savings_pct, 2),
+            self.num_projects.to_string(),
+        ]
+    }
+
+    fn sort_values(&self) -> Vec<SortValue> {
+        vec![
+            SortValue::Number(self.funding_year as f64),
+            SortValue::Text(self.main_island.clone()),
+            SortValue::Number(self.total_approved_budget),
+            SortValue::Number(self.total_contract_cost),
+            SortValue::Number(self.total_savings),
+            SortValue::Number(self.savings_pct),
+            SortValue::Number(self.num_projects as f64),
+        ]
+    }
+}
+
+/// Generate Report 8: Funding-Year Budget vs Contract Cost by Island Group
+/// The budget, cost and savings totals are shown with `budget_decimals` decimal places.
+pub fn generate_report8(records: &[ProcessedRecord], budget_decimals: usize) -> Vec<Report8Temp> {
+    // Group projects by year + island group
+    let grouped = group_by(records, |r| (r.funding_year, r.main_island.clone()));
+
+    let mut temp: Vec<Report8Temp> = grouped.into_iter().map(|((funding_year, main_island), recs)| {
+        let total_approved_budget: f64 = recs.iter().map(|r| r.approved_budget_for_contract).sum();
+        let total_contract_cost: f64 = recs.iter().map(|r| r.contract_cost).sum();
+        let total_savings: f64 = recs.iter().map(|r| r.cost_savings).sum();
+        Report8Temp {
+            funding_year,
+            main_island,
+            total_approved_budget,
+            total_contract_cost,
+            total_savings,
+            savings_pct: calculate_percentage(total_savings, total_approved_budget),
+            num_projects: recs.len(),
+            budget_decimals,
+        }
+    }).collect();
+
+    // Sort by year, then island group
+    temp.sort_by(|a, b| a.funding_year.cmp(&b.funding_year).then_with(|| a.main_island.cmp(&b.main_island)));
+    temp
+}
+
+// ============================================================================
+// REPORT GENERATION - REPORT 9: QUARTERLY TRENDS
+// ============================================================================
+
+/// One row of Report 9: projects started in one quarter of a funding year.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct Report9Temp {
+    pub funding_year: i32,
+    // Calendar quarter (1-4) of the start date.
+    pub quarter: u8,
+    pub num_projects: usize,
+    pub total_budget: f64,
+    pub avg_delay: f64,
+    pub overrun_rate: f64,
+    // Decimal places for the budget total in the formatted cells.
+    #[serde(skip)]
+    pub budget_decimals: usize,
+}
+
+impl ReportRecord for Report9Temp {
+    const HEADERS: &'static [&'static str] = &[
+        "FundingYear", "Quarter", "NumProjects", "TotalBudget", "AvgDelay", "OverrunRate",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.funding_year.to_string(),
+            format!("Q{}", self.quarter),
+            self.num_projects.to_string(),
+            format_number(self.total_budget, self.budget_decimals),
+            format_number(self.avg_delay, 2),
+            format_number(self.overrun_rate, 2),
+        ]
+    }
+
+    fn sort_values(&self) -> Vec<SortValue> {
+        vec![
+            SortValue::Number(self.funding_year as f64),
+            SortValue::Number(self.quarter as f64),
+            SortValue::Number(self.num_projects as f64),
+            SortValue::Number(self.total_budget),
+            SortValue::Number(self.avg_delay),
+            SortValue::Number(self.overrun_rate),
+        ]
+    }
+}
+
+/// Generate Report 9: Quarterly Trends by Start Date
+/// Splits each funding year into quarters of the project start date, so seasonal
+/// budget spikes and overruns show up. Projects without a start date are left out.
+/// The budget total is shown with `budget_decimals` decimal places.
+pub fn generate_report9(records: &[ProcessedRecord], budget_decimals: usize) -> Vec<Report9Temp> {
+    // Group dated projects by year + start quarter
+    let dated: Vec<&ProcessedRecord> = records.iter().filter(|r| r.start_date.is_some()).collect();
+    let grouped = group_by(&dated, |r| (r.funding_year, r.start_date.map_or(0, extract_quarter)));
+
+    let mut temp: Vec<Report9Temp> = grouped.into_iter().map(|((funding_year, quarter), recs)| {
+        let delays: Vec<i64> = recs.iter().filter_map(|r| r.completion_delay_days).collect();
+        let overruns = recs.iter().filter(|r| r.cost_savings < 0.0).count();
+        Report9Temp {
+            funding_year,
+            quarter,
+            num_projects: recs.len(),
+            total_budget: recs.iter().map(|r| r.approved_budget_for_contract).sum(),
+            avg_delay: calculate_average_i64(&delays),
+            overrun_rate: calculate_percentage(overruns as f64, recs.len() as f64),
+            budget_decimals,
+        }
+    }).collect();
+
+    // Sort by year, then quarter
+    temp.sort_by_key(|r| (r.funding_year, r.quarter));
+    temp
+}
+
+// ============================================================================
+// CONTRACTOR DRILL-DOWN
+// ============================================================================
+
+/// One row of a contractor drill-down: a single project of that contractor.
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContractorProjectRow {
+    pub funding_year: i32,
+    pub province: String,
+    pub type_of_work: String,
+    pub contract_cost: f64,
+    pub cost_savings: f64,
+    pub delay_days: Option<i64>,
+}
+
+impl ReportRecord for ContractorProjectRow {
+    const HEADERS: &'static [&'static str] = &[
+        "FundingYear", "Province", "TypeOfWork", "ContractCost", "CostSavings", "DelayDays",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.funding_year.to_string(),
+            self.province.clone(),
+            self.type_of_work.clone(),
+            format_number(self.contract_cost, 2),
+            format_number(self.cost_savings, 2),
+            self.delay_days.map_or("N/A".to_string(), |d| d.to_string()),
+        ]
+    }
+
+    fn sort_values(&self) -> Vec<SortValue> {
+        vec![
+            SortValue::Number(self.funding_year as f64),
+            SortValue::Text(self.province.clone()),
+            SortValue::Text(self.type_of_work.clone()),
+            SortValue::Number(self.contract_cost),
+            SortValue::Number(self.cost_savings),
+            self.delay_days.map_or(SortValue::Missing, |d| SortValue::Number(d as f64)),
+        ]
+    }
+}
+
+/// Every project of `contractor` (exact name), oldest year first, then by province,
+/// together with the contractor's reliability index computed as in Report 2.
+/// Unlike Report 2, contractors with fewer than five projects are included.
+pub fn generate_contractor_drilldown(records: &[ProcessedRecord], contractor: &str) -> (Vec<ContractorProjectRow>, f64) {
+    let projects: Vec<&ProcessedRecord> = records.iter().filter(|r| r.contractor == contractor).collect();
+
+    let total_cost: f64 = projects.iter().map(|r| r.contract_cost).sum();
+    let total_savings: f64 = projects.iter().map(|r| r.cost_savings).sum();
+    let delays: Vec<i64> = projects.iter().filter_map(|r| r.completion_delay_days).collect();
+    let reliability_index = calculate_reliability_index(total_cost, total_savings, calculate_average_i64(&delays));
+
+    let mut rows: Vec<ContractorProjectRow> = projects.iter().map(|r| ContractorProjectRow {
+        funding_year: r.funding_year,
+        province: r.province.clone(),
+        type_of_work: r.type_of_work.clone(),
+        contract_cost: r.contract_cost,
+        cost_savings: r.cost_savings,
+        delay_days: r.completion_delay_days,
+    }).collect();
+    rows.sort_by(|a, b| a.funding_year.cmp(&b.funding_year).then_with(|| a.province.cmp(&b.province)));
+    (rows, reliability_index)
+}
+
+// ============================================================================
+// REPORT SORTING
+// ============================================================================
+
+/// Column headers of a report by its menu number ("1", "2", "3", "6", "8" or "9").
+pub fn report_headers(report: &str) -> Option<&'static [&'static str]> {
+    match report {
+        "1" => Some(Report1Temp::HEADERS),
+        "2" => Some(Report2Temp::HEADERS),
+        "3" => Some(Report3Temp::HEADERS),
+        "6" => Some(Report6Temp::HEADERS),
+        "8" => Some(Report8Temp::HEADERS),
+        "9" => Some(Report9Temp::HEADERS),
+        _ => None,
+    }
+}
+
+/// Orders two raw column values: numbers numerically, text alphabetically.
+/// Missing values come after everything else in either direction.
+fn compare_sort_values(a: &SortValue, b: &SortValue, descending: bool) -> Ordering {
+    let ordering = match (a, b) {
+        (SortValue::Missing, SortValue::Missing) => return Ordering::Equal,
+        (SortValue::Missing, _) => return Ordering::Greater,
+        (_, SortValue::Missing) => return Ordering::Less,
+        (SortValue::Number(x), SortValue::Number(y)) => x.total_cmp(y),
+        (SortValue::Text(x), SortValue::Text(y)) => x.cmp(y),
+        (SortValue::Number(_), SortValue::Text(_)) => Ordering::Less,
+        (SortValue::Text(_), SortValue::Number(_)) => Ordering::Greater,
+    };
+    if descending { ordering.reverse() } else { ordering }
+}
+
+/// Reorders a report's rows by `sort.column`, comparing the rows' unformatted values,
+/// so 1200 sorts after 950. The sort is stable: rows with equal values keep the order
+/// the report generated them in, and the rank column is left as computed.
+/// A column the report does not have leaves the order unchanged.
+pub fn sort_report_rows<R: ReportRecord>(rows: &mut Vec<R>, sort: &ReportSort) {
+    let Some(index) = R::HEADERS.iter().position(|&h| h == sort.column) else {
+        return;
+    };
+    let mut keyed: Vec<(SortValue, R)> = rows
+        .drain(..)
+        .map(|row| (row.sort_values().swap_remove(index), row))
+        .collect();
+    keyed.sort_by(|(a, _), (b, _)| compare_sort_values(a, b, sort.descending));
+    rows.extend(keyed.into_iter().map(|(_, row)| row));
+}
+
+/// Applies the last sort chosen for `report`, if any; otherwise the report keeps its own order.
+pub fn apply_report_sort<R: ReportRecord>(rows: &mut Vec<R>, sorts: &[ReportSort], report: &str) {
+    if let Some(sort) = sorts.iter().rev().find(|s| s.report == report) {
+        info!("Report {} sorted by {}", report, sort);
+        sort_report_rows(rows, sort);
+    }
+}
+
+// ============================================================================
+// SUMMARY GENERATION
+// ============================================================================
+
+/// Generate summary JSON with aggregate statistics
+/// `start_year`..=`end_year` is the analysed range used for the per-year breakdown.
+/// `status` is "empty" when there are no records, and every metric but `total_projects` is then null.
+pub fn generate_summary(records: &[ProcessedRecord], start_year: i32, end_year: i32) -> JsonValue {
+    // Collect unique contractors, excluding empty and "Unknown" entries.
+    let unique_contractors: HashSet<String> = records
+        .iter()
+        .map(|r| r.contractor.clone())
+        .filter(|c| !c.is_empty() && c != "Unknown")
+        .collect();
+
+    // Collect unique provinces from valid records.
+    let unique_provinces: HashSet<String> = records
+        .iter()
+        .map(|r| r.province.clone())
+        .filter(|p| !p.is_empty())
+        .collect();
+
+    // Extract all valid delay values and total cost savings for computation.
+    let delays: Vec<i64> = records.iter().filter_map(|r| r.completion_delay_days).collect();
+    let total_savings: f64 = records.iter().map(|r| r.cost_savings).sum();
+
+    // National percentiles of cost savings and delay.
+    let savings: Vec<f64> = records.iter().map(|r| r.cost_savings).collect();
+    let delays_f64: Vec<f64> = delays.iter().map(|&d| d as f64).collect();
+    let percentiles = |values: &[f64]| {
+        let at = |p: f64| (calculate_percentile(values, p) * 100.0).round() / 100.0;
+        json!({ "p25": at(25.0), "p50": at(50.0), "p75": at(75.0), "p90": at(90.0) })
+    };
+
+    // Relative spread of budgets and delays; null when the mean is zero.
+    let budgets: Vec<f64> = records.iter().map(|r| r.approved_budget_for_contract).collect();
+    let cv = |values: &[f64]| calculate_coefficient_of_variation(values).map(|cv| (cv * 10000.0).round() / 10000.0);
+
+    // Gini coefficient of total approved budget across provinces and regions.
+    let mut province_budgets: HashMap<String, f64> = HashMap::new();
+    let mut region_budgets: HashMap<String, f64> = HashMap::new();
+    for r in records {
+        if !r.province.is_empty() {
+            *province_budgets.entry(r.province.clone()).or_default() += r.approved_budget_for_contract;
+        }
+        *region_budgets.entry(r.region.clone()).or_default() += r.approved_budget_for_contract;
+    }
+    let gini = |budgets: &HashMap<String, f64>| {
+        let totals: Vec<f64> = budgets.values().copied().collect();
+        (calculate_gini(&totals) * 10000.0).round() / 10000.0
+    };
+
+    // Per-year breakdown; years without data still appear with zeros.
+    let mut by_year = serde_json::Map::new();
+    for year in start_year..=end_year {
+        let year_records: Vec<&ProcessedRecord> = records.iter().filter(|r| r.funding_year == year).collect();
+        let year_delays: Vec<i64> = year_records.iter().filter_map(|r| r.completion_delay_days).collect();
+        let year_budget: f64 = year_records.iter().map(|r| r.approved_budget_for_contract).sum();
+        let year_savings: f64 = year_records.iter().map(|r| r.cost_savings).sum();
+        let overruns = year_records.iter().filter(|r| r.cost_savings < 0.0).count();
+        let overrun_rate = calculate_percentage(overruns as f64, year_records.len() as f64);
+        by_year.insert(year.to_string(), json!({
+            "avg_delay": ((calculate_average_i64(&year_delays) * 10.0).round() / 10.0),
+            "overrun_rate": ((overrun_rate * 100.0).round() / 100.0),
+            "total_budget": year_budget.round(),
+            "total_projects": year_records.len(),
+            "total_savings": year_savings.round()
+        }));
+    }
+
+    // Running totals per year as [year, cumulative] pairs.
+    let cumulative = |series: Vec<(i32, f64)>| {
+        series.into_iter().map(|(year, total)| json!([year, total.round()])).collect::<Vec<_>>()
+    };
+
+    // Count projects whose completion date precedes their start date.
+    let negative_delay_count = records.iter().filter(|r| has_negative_delay(r)).count();
+
+    // Number of projects at each overrun severity level.
+    let mut overrun_severity_counts = serde_json::Map::new();
+    for severity in OverrunSeverity::ALL {
+        let count = records.iter().filter(|r| compute_overrun_severity(r) == severity).count();
+        overrun_severity_counts.insert(severity.label().to_string(), json!(count));
+    }
+
+    // Construct a JSON summary using serde_json's `json!` macro.
+    let mut summary = json!({
+        "budget_cv": cv(&budgets),
+        "budget_weighted_avg_delay": ((compute_budget_weighted_delay(records) * 10.0).round() / 10.0),
+        "by_year": by_year,
+        "cumulative_budget": cumulative(calculate_cumulative_budget(records, true)),
+        "cumulative_savings": cumulative(calculate_cumulative_savings(records, true)),
+        "global_avg_delay": ((calculate_average_i64(&delays) * 10.0).round() / 10.0),
+        "delay_cv": cv(&delays_f64),
+        "delay_percentiles": percentiles(&delays_f64),
+        "negative_delay_count": negative_delay_count,
+        "overrun_severity_counts": overrun_severity_counts,
+        "province_budget_gini": gini(&province_budgets),
+        "region_budget_gini": gini(&region_budgets),
+        "savings_percentiles": percentiles(&savings),
+        "severe_delay_count": detect_severe_delays(records).len(),
+        "total_contractors": unique_contractors.len(),
+        "total_projects": records.len(),
+        "total_provinces": unique_provinces.len(),
+        "total_savings": total_savings.round()
+    });
+
+    // Averages, ratios and percentiles of nothing would read as real zeros, so they become null.
+    if let Some(fields) = summary.as_object_mut() {
+        if records.is_empty() {
+            for (key, value) in fields.iter_mut() {
+                if key != "total_projects" {
+                    *value = JsonValue::Null;
+                }
+            }
+        }
+        let status = if records.is_empty() { "empty" } else { "ok" };
+        fields.insert("status".to_string(), json!(status));
+        fields.insert("imputation_strategy".to_string(), imputation_strategy());
+    }
+    summary
+}
+
+/// How the median-imputed copy of the cleaned records is filled in, for the summary.
+/// Describes the method rather than the data, so it is kept even for an empty summary.
+pub fn imputation_strategy() -> JsonValue {
+    json!({
+        "method": "median",
+        "medians_from": "loaded records that have both coordinates, a start date and an actual completion date",
+        "fields": {
+            "latitude": "median latitude",
+            "longitude": "median longitude",
+            "start_date": "actual completion date minus the median completion delay",
+            "actual_completion_date": "start date plus the median completion delay"
+        },
+        "not_imputed": {
+            "budget": "rows without a valid budget are rejected while cleaning",
+            "dates": "records missing both dates keep both empty"
+        },
+        "outputs": {
+            "strict": "cleaned_records.json",
+            "imputed": "cleaned_records_imputed.json",
+            "written_by": "--export-cleaned, or menu option 6b"
+        },
+        "reports_use": "the strict records; missing coordinates in reports take their province average instead"
+    })
+}
+
+/// Write summary to JSON file
+pub fn write_summary(summary_data: &JsonValue, output_dir: &Path, filename: &str, dry_run: bool) -> Result<PathBuf, PipelineError> {
+    let file_path = output_dir.join(filename);
+    if dry_run {
+        println!("Dry run: would write summary to {}", file_path.display());
+        return Ok(file_path);
+    }
+    write_json(&file_path, summary_data)?;
+    info!("Summary written to: {}", file_path.display());
+    Ok(file_path)
+}
+
+// ============================================================================
+// PRETTY REPORT WRITER WITH PREVIEW
+// ============================================================================
+
+/// Shortens `value` to at most `max_width` characters, ending with "…" when cut.
+pub fn truncate_cell(value: &str, max_width: usize) -> String {
+    if value.chars().count() <= max_width {
+        value.to_string()
+    } else {
+        let kept: String = value.chars().take(max_width.saturating_sub(1)).collect();
+        format!("{}…", kept)
+    }
+}
+
+/// Whether every filled-in value of a column is a number (or percentage),
+/// so the preview can right-align it. Dashes and "N/A" placeholders are skipped.
+pub fn is_numeric_column(values: &[&str]) -> bool {
+    let mut saw_number = false;
+    for value in values {
+        let value = value.trim();
+        if value.is_empty() || value == "-" || value == "N/A" {
+            continue;
+        }
+        if validate_number(value.trim_end_matches('%')).is_none() {
+            return false;
+        }
+        saw_number = true;
+    }
+    saw_number
+}
+
+/// Whether a formatted cell holds a number below zero.
+pub fn is_negative(value: &str) -> bool {
+    validate_number(value.trim_end_matches('%')).is_some_and(|n| n < 0.0)
+}
+
+/// Shrinks the widest text columns one character at a time until the table fits
+/// in `available` columns or every text column is down to `min_width`.
+/// `widths` are content widths; each column adds 3 characters of padding and border.
+pub fn fit_column_widths(widths: &mut [usize], shrinkable: &[bool], available: usize, min_width: usize) {
+    let table_width = |widths: &[usize]| widths.iter().map(|w| w + 3).sum::<usize>() + 1;
+    while table_width(widths) > available {
+        let widest = (0..widths.len())
+            .filter(|&i| shrinkable[i] && widths[i] > min_width)
+            .max_by_key(|&i| widths[i]);
+        match widest {
+            Some(i) => widths[i] -= 1,
+            None => break,
+        }
+    }
+}
+
+/// Prints a horizontal bar chart of a report column, one line per row.
+/// Bars are scaled so the largest value fills `BAR_MAX_WIDTH`; zero or negative values get no bar.
+pub fn print_bar_chart<R: ReportRecord>(title: &str, data: &[R], label_column: &str, value_column: &str) {
+    let bars: Vec<(String, f64)> = data
+        .iter()
+        .map(|row| {
+            let label = row.cell(label_column).unwrap_or_default();
+            let value = row.cell(value_column).and_then(|v| validate_number(&v)).unwrap_or(0.0);
+            (label, value)
+        })
+        .collect();
+    let max = bars.iter().map(|(_, v)| *v).fold(0.0, f64::max);
+
+    println!("{}", title);
+    for (label, value) in &bars {
+        let length = if max > 0.0 && *value > 0.0 {
+            ((value / max) * BAR_MAX_WIDTH as f64).round() as usize
+        } else {
+            0
+        };
+        println!(
+            "{:>width$} │ {:<bar_width$} {:.2}",
+            truncate_cell(label, BAR_LABEL_WIDTH),
+            "█".repeat(length),
+            value,
+            width = BAR_LABEL_WIDTH,
+            bar_width = BAR_MAX_WIDTH,
+        );
+    }
+}
+
+/// Box-drawing table format shared by the console tables.
+pub fn console_table_format() -> format::TableFormat {
+    format::FormatBuilder::new()
+        .column_separator('│')
+        .borders('│')
+        .separators(&[format::LinePosition::Top], format::LineSeparator::new('─', '┬', '┌', '┐'))
+        .separators(&[format::LinePosition::Intern], format::LineSeparator::new('─', '┼', '├', '┤'))
+        .separators(&[format::LinePosition::Bottom], format::LineSeparator::new('─', '┴', '└', '┘'))
+        .padding(1, 1)
+        .build()
+}
+
+/// Builds a grand-total row for a report, with cells in `R::HEADERS` order.
+/// `label` goes in `label_column`, the `sum_columns` are added up across all rows,
+/// and every other column shows a dash (summing averages would be misleading).
+/// Each sum keeps the most decimal places used by that column's values.
+pub fn build_total_row<R: ReportRecord>(
+    data: &[R],
+    label_column: &str,
+    label: String,
+    sum_columns: &[&str],
+) -> Vec<String> {
+    let rows: Vec<Vec<String>> = data.iter().map(R::cells).collect();
+    R::HEADERS.iter().enumerate().map(|(c, &h)| {
+        if h == label_column {
+            label.clone()
+        } else if sum_columns.contains(&h) {
+            let values: Vec<&String> = rows.iter().map(|row| &row[c]).collect();
+            // Folding from 0.0 keeps an empty report's total at "0"; `sum()` of nothing is -0.0.
+            let sum = values.iter().filter_map(|v| validate_number(v)).fold(0.0, |total, v| total + v);
+            let decimals = values
+                .iter()
+                .map(|v| v.split_once('.').map_or(0, |(_, fraction)| fraction.len()))
+                .max()
+                .unwrap_or(0);
+            format_number(sum, decimals)
+        } else {
+            "-".to_string()
+        }
+    }).collect()
+}
+
+/// Writes a report's header row, its records and the `total` row as delimited text
+/// to any writer. An empty `total` leaves the total row out.
+pub fn write_report_to_writer<W: Write, R: ReportRecord>(
+    writer: W,
+    data: &[R],
+    total: &[String],
+    delimiter: Delimiter,
+    excel_compat: bool,
+) -> Result<(), PipelineError> {
+    let total_row = (!total.is_empty()).then(|| total.to_vec());
+    write_rows_to_writer(writer, R::HEADERS, data.iter().map(R::cells).chain(total_row), delimiter, excel_compat)
+}
+
+/// Writes a report's records to `file_path` as a JSON array of objects keyed by column
+/// header. Numbers stay unformatted, so charting tools read them as numbers.
+pub fn write_report_json<R: ReportRecord>(file_path: &PathBuf, data: &[R]) -> Result<(), PipelineError> {
+    write_json(file_path, &serde_json::to_value(data).map_err(io::Error::from)?)?;
+    info!("Report written to: {}", file_path.display());
+    Ok(())
+}
+
+/// Generic function to write report to CSV with preview
+/// The typed records also go to a JSON file beside the CSV, with unformatted numbers.
+/// A report without rows writes nothing unless `output.write_empty` is set, and has no preview.
+pub fn write_report<R: ReportRecord>(
+    output_dir: &Path,
+    filename: &str,
+    data: &[R],
+    report_title: &str,
+    preview: &PreviewConfig,
+    output: &OutputSettings,
+    total: &[String],
+) -> Result<PathBuf, PipelineError> {
+    // Construct full file path inside the output directory.
+    let file_path = output_dir.join(filename);
+    let headers = R::HEADERS;
+
+    // Write the data to CSV file, followed by the grand-total row.
+    // A tab-separated copy goes next to it for names that contain commas.
+    let rows_with_total: Vec<Vec<String>> = data.iter().map(R::cells).chain(std::iter::once(total.to_vec())).collect();
+    let tsv_path = file_path.with_extension("tsv");
+    let json_path = file_path.with_extension("json");
+
+    // An empty report is skipped unless `--write-empty` asks for header-only files.
+    if data.is_empty() && !output.write_empty {
+        let stale = [&file_path, &tsv_path, &json_path].iter().any(|path| path.exists());
+        println!(
+            "No rows to report — skipping {}{}",
+            filename,
+            if stale { " (the file already there is from an earlier run)" } else { "" }
+        );
+        return Ok(file_path);
+    }
+    if output.format.writes_csv() {
+        for (path, delimiter) in [(&file_path, Delimiter::Comma), (&tsv_path, Delimiter::Tab)] {
+            if output.dry_run {
+                println!("Dry run: would write {} rows to {}", rows_with_total.len(), path.display());
+                continue;
+            }
+            ensure_dir(path)?;
+            let file = io::BufWriter::new(fs::File::create(path)?);
+            write_report_to_writer(file, data, total, delimiter, output.excel_compat)?;
+            info!("Report written to: {}", path.display());
+        }
+    }
+    if output.format.writes_json() {
+        if output.dry_run {
+            println!("Dry run: would write {} records to {}", data.len(), json_path.display());
+        } else {
+            write_report_json(&json_path, data)?;
+        }
+    }
+    // The file named in the one-line summary: the CSV, unless only JSON was written.
+    let file_path = if output.format.writes_csv() { file_path } else { json_path };
+
+    // There is nothing to preview in a header-only report.
+    if data.is_empty() {
+        println!("No rows to report — wrote header-only {}", file_path.display());
+        return Ok(file_path);
+    }
+
+    // Quiet mode prints one line per report instead of the table.
+    if preview.quiet && output.dry_run {
+        return Ok(file_path);
+    }
+    if preview.quiet {
+        println!("{}: {} rows -> {}", report_title, data.len(), file_path.display());
+        return Ok(file_path);
+    }
+
+    // A preview size of zero turns the console table off.
+    if preview.max_rows == 0 {
+        println!();
+        return Ok(file_path);
+    }
+
+    // Print formatted table preview (first `max_rows` rows).
+    println!("\n{} (preview)", report_title);
+
+    // Collect the visible text first so column widths can be capped before rendering;
+    // the CSV written above keeps the full values.
+    let data_rows = &rows_with_total[..data.len()];
+    let header_labels: Vec<String> = headers.iter().enumerate().map(|(c, &h)| {
+        if preview.show_column_widths {
+            let width = data_rows
+                .iter()
+                .map(|row| row[c].chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(h.chars().count());
+            format!("{} ({})", h, width)
+        } else {
+            h.to_string()
+        }
+    }).collect();
+    let shown = &data_rows[..data.len().min(preview.max_rows)];
+    let body: Vec<Vec<String>> = shown.iter().cloned().chain(std::iter::once(total.to_vec())).collect();
+
+    // The total row's label ("TOTAL ...") should not stop a column counting as numeric.
+    let numeric: Vec<bool> = (0..headers.len())
+        .map(|c| is_numeric_column(&body[..shown.len()].iter().map(|row| row[c].as_str()).collect::<Vec<_>>()))
+        .collect();
+    let mut widths: Vec<usize> = (0..headers.len())
+        .map(|c| {
+            body.iter()
+                .map(|row| &row[c])
+                .chain(std::iter::once(&header_labels[c]))
+                .map(|v| v.chars().count())
+                .max()
+                .unwrap_or(0)
+                .min(preview.max_column_width)
+        })
+        .collect();
+    // Only text columns give up width; truncated numbers would be misleading.
+    if let Some((terminal_size::Width(columns), _)) = terminal_size::terminal_size() {
+        let shrinkable: Vec<bool> = numeric.iter().map(|n| !n).collect();
+        fit_column_widths(&mut widths, &shrinkable, columns as usize, 8);
+    }
+
+    let mut table = Table::new();
+    table.set_format(console_table_format());
+
+    // Add header row with bold and green style, optionally annotated with column widths.
+    let header_cells: Vec<Cell> = header_labels.iter().enumerate().map(|(c, label)| {
+        Cell::new(&truncate_cell(label, widths[c])).style_spec(if preview.use_color { "bFg" } else { "b" })
+    }).collect();
+    table.add_row(Row::new(header_cells));
+
+    // Display only the first few rows to prevent overflow; numeric columns are right-aligned.
+    let (rows, total_row) = body.split_at(shown.len());
+    for row in rows {
+        let cells: Vec<Cell> = row.iter().enumerate().map(|(c, value)| {
+            let cell = Cell::new(&truncate_cell(value, widths[c]));
+            // Highlight risk labels and negative numbers so they stand out.
+            match (preview.use_color, headers[c], value.as_str()) {
+                (true, "RiskFlag", "High Risk") => cell.style_spec("Fr"),
+                (true, "RiskFlag", "Low Risk") => cell.style_spec("Fg"),
+                (true, _, _) if numeric[c] && is_negative(value) => cell.style_spec("Frr"),
+                _ if numeric[c] => cell.style_spec("r"),
+                _ => cell,
+            }
+        }).collect();
+        table.add_row(Row::new(cells));
+    }
+
+    // Always show the grand-total row last, in bold.
+    let total_cells: Vec<Cell> = total_row[0].iter().enumerate().map(|(c, value)| {
+        let spec = match (numeric[c], preview.use_color && is_negative(value)) {
+            (true, true) => "bFrr",
+            (true, false) => "br",
+            _ => "b",
+        };
+        Cell::new(&truncate_cell(value, widths[c])).style_spec(spec)
+    }).collect();
+    table.add_row(Row::new(total_cells));
+
+    // Print formatted table to console.
+    table.printstd();
+
+    // Indicate if there are more rows.
+    if preview.show_row_count && data.len() > preview.max_rows {
+        println!("... ({} more rows)", data.len() - preview.max_rows);
+    }
+    println!();
+
+    Ok(file_path)
+}
+
+/// Writes the HTML version of a report (including its total row) into the output directory.
+pub fn write_html_page<R: ReportRecord>(
+    output_dir: &Path,
+    filename: &str,
+    data: &[R],
+    total: &[String],
+    title: &str,
+    output: &OutputSettings,
+) -> Result<PathBuf, PipelineError> {
+    let file_path = output_dir.join(filename);
+    // `write_report` has already said why an empty report is skipped.
+    if data.is_empty() && !output.write_empty {
+        return Ok(file_path);
+    }
+    if output.dry_run {
+        println!("Dry run: would write {} rows to {}", data.len() + 1, file_path.display());
+        return Ok(file_path);
+    }
+    let rows_with_total: Vec<Vec<String>> = data.iter().map(R::cells).chain(std::iter::once(total.to_vec())).collect();
+    write_html_report(&file_path, &rows_with_total, R::HEADERS, title)?;
+    info!("HTML report written to: {}", file_path.display());
+    Ok(file_path)
+}
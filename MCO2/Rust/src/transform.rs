@@ -0,0 +1,424 @@
+//! Derived fields, de-duplication, imputation and record filters.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use chrono::prelude::*;
+
+use crate::model::{CleanedRecord, ProcessedRecord, OverrunSeverity, CoordinateConflict, DatasetMedians};
+use crate::config::RecordFilter;
+use crate::compute::{calculate_average, calculate_median, calculate_std_dev};
+
+// ============================================================================
+// UTILITY FUNCTIONS - TRANSFORMATION
+// ============================================================================
+
+/// Classifies a project's overrun as a percentage of its approved budget.
+/// No overrun (savings of zero or more) is `None`; an overrun on a zero budget is `Severe`.
+pub fn compute_overrun_severity(record: &ProcessedRecord) -> OverrunSeverity {
+    if record.cost_savings >= 0.0 {
+        return OverrunSeverity::None;
+    }
+    if record.approved_budget_for_contract <= 0.0 {
+        return OverrunSeverity::Severe;
+    }
+    let overrun_pct = -record.cost_savings / record.approved_budget_for_contract * 100.0;
+    if overrun_pct <= 5.0 {
+        OverrunSeverity::Minor
+    } else if overrun_pct <= 20.0 {
+        OverrunSeverity::Moderate
+    } else {
+        OverrunSeverity::Severe
+    }
+}
+
+/// Computes savings (budget - cost).
+pub fn calculate_cost_savings(approved_budget: f64, contract_cost: f64) -> f64 {
+    approved_budget - contract_cost
+}
+
+/// Computes project duration (in days) if both dates are available.
+pub fn calculate_completion_delay(
+    start_date: Option<NaiveDate>,
+    completion_date: Option<NaiveDate>,
+) -> Option<i64> {
+    match (start_date, completion_date) {
+        (Some(start), Some(completion)) => Some((completion - start).num_days()),
+        _ => None,
+    }
+}
+
+/// Calendar quarter (1-4) that `date` falls in.
+pub fn extract_quarter(date: NaiveDate) -> u8 {
+    (date.month0() / 3 + 1) as u8
+}
+
+/// Returns the date `fraction` of the way from `start` to `end`, rounded to the nearest day.
+/// 0.0 gives `start` and 1.0 gives `end`; fractions outside that range extrapolate.
+pub fn interpolate_missing_date(start: NaiveDate, end: NaiveDate, fraction: f64) -> NaiveDate {
+    let days = ((end - start).num_days() as f64 * fraction).round() as i64;
+    start + chrono::Duration::days(days)
+}
+
+/// Completion date of a project, estimated as `start_date` plus `avg_delay_days`
+/// when the actual completion date is missing. Returns the actual date when it is known,
+/// and `None` when there is no start date to estimate from.
+pub fn estimate_completion_date(record: &ProcessedRecord, avg_delay_days: f64) -> Option<NaiveDate> {
+    if record.actual_completion_date.is_some() {
+        return record.actual_completion_date;
+    }
+    let start = record.start_date?;
+    Some(start + chrono::Duration::days(avg_delay_days.round() as i64))
+}
+
+/// Adds derived fields (savings, delay) to a cleaned record.
+pub fn add_derived_fields(record: CleanedRecord) -> ProcessedRecord {
+    let cost_savings = calculate_cost_savings(
+        record.approved_budget_for_contract,
+        record.contract_cost,
+    );
+    let completion_delay = calculate_completion_delay(
+        record.start_date,
+        record.actual_completion_date,
+    );
+    ProcessedRecord {
+        region: record.region,
+        main_island: record.main_island,
+        funding_year: record.funding_year,
+        approved_budget_for_contract: record.approved_budget_for_contract,
+        contract_cost: record.contract_cost,
+        start_date: record.start_date,
+        actual_completion_date: record.actual_completion_date,
+        project_latitude: record.project_latitude,
+        project_longitude: record.project_longitude,
+        province: record.province,
+        contractor: record.contractor,
+        type_of_work: record.type_of_work,
+        cost_savings,
+        completion_delay_days: completion_delay,
+        coordinates_imputed: false,
+        source_file: record.source_file,
+    }
+}
+
+/// The cleaned record behind a processed one: derived fields are dropped, and so are
+/// coordinates filled in from province averages (both, as the record only flags that one was).
+pub fn strip_derived_fields(record: &ProcessedRecord) -> CleanedRecord {
+    let (project_latitude, project_longitude) = if record.coordinates_imputed {
+        (None, None)
+    } else {
+        (record.project_latitude, record.project_longitude)
+    };
+    CleanedRecord {
+        region: record.region.clone(),
+        main_island: record.main_island.clone(),
+        funding_year: record.funding_year,
+        approved_budget_for_contract: record.approved_budget_for_contract,
+        contract_cost: record.contract_cost,
+        start_date: record.start_date,
+        actual_completion_date: record.actual_completion_date,
+        project_latitude,
+        project_longitude,
+        province: record.province.clone(),
+        contractor: record.contractor.clone(),
+        type_of_work: record.type_of_work.clone(),
+        source_file: record.source_file.clone(),
+    }
+}
+
+/// Medians of the coordinates and completion delay over the records that have both
+/// coordinates and both dates. Budgets need none: rows without a valid budget are rejected while cleaning.
+pub fn compute_dataset_medians(records: &[CleanedRecord]) -> DatasetMedians {
+    let complete: Vec<&CleanedRecord> = records
+        .iter()
+        .filter(|r| {
+            r.project_latitude.is_some() && r.project_longitude.is_some() && r.start_date.is_some() && r.actual_completion_date.is_some()
+        })
+        .collect();
+    if complete.is_empty() {
+        return DatasetMedians::default();
+    }
+    let latitudes: Vec<f64> = complete.iter().filter_map(|r| r.project_latitude).collect();
+    let longitudes: Vec<f64> = complete.iter().filter_map(|r| r.project_longitude).collect();
+    let delays: Vec<f64> = complete
+        .iter()
+        .filter_map(|r| calculate_completion_delay(r.start_date, r.actual_completion_date))
+        .map(|d| d as f64)
+        .collect();
+    DatasetMedians {
+        latitude: Some(calculate_median(&latitudes)),
+        longitude: Some(calculate_median(&longitudes)),
+        delay_days: Some(calculate_median(&delays)),
+        complete_records: complete.len(),
+    }
+}
+
+/// The median-imputed copy of the cleaned dataset; see `CleanedRecord::with_defaults`.
+pub fn fill_missing_with_medians(records: Vec<CleanedRecord>, medians: &DatasetMedians) -> Vec<CleanedRecord> {
+    records.into_iter().map(|record| record.with_defaults(medians)).collect()
+}
+
+/// Checks whether a project's completion date falls before its start date.
+pub fn has_negative_delay(record: &ProcessedRecord) -> bool {
+    match (record.start_date, record.actual_completion_date) {
+        (Some(start), Some(completion)) => completion < start,
+        _ => false,
+    }
+}
+
+/// Flags records that were completed before they started.
+/// Optionally nulls out their delay so impossible durations don't skew averages.
+/// Returns the records along with the number of flagged rows.
+pub fn flag_negative_delays(mut records: Vec<ProcessedRecord>, null_out: bool) -> (Vec<ProcessedRecord>, usize) {
+    let mut flagged = 0;
+    for record in &mut records {
+        if has_negative_delay(record) {
+            flagged += 1;
+            if null_out {
+                record.completion_delay_days = None;
+            }
+        }
+    }
+    (records, flagged)
+}
+
+// Delays more than this many standard deviations above the mean count as severe.
+pub const SEVERE_DELAY_Z_SCORE: f64 = 2.0;
+
+/// Mean and sample standard deviation of the completion delays in days.
+/// Records without a delay are left out.
+pub fn delay_mean_and_std_dev(records: &[ProcessedRecord]) -> (f64, f64) {
+    let delays: Vec<f64> = records.iter().filter_map(|r| r.completion_delay_days).map(|d| d as f64).collect();
+    (calculate_average(&delays), calculate_std_dev(&delays))
+}
+
+/// Finds records whose delay is more than `SEVERE_DELAY_Z_SCORE` standard deviations above the mean.
+/// Returns their indices, longest delay first; equal delays keep their input order.
+pub fn detect_severe_delays(records: &[ProcessedRecord]) -> Vec<usize> {
+    let (mean, std_dev) = delay_mean_and_std_dev(records);
+    let threshold = mean + SEVERE_DELAY_Z_SCORE * std_dev;
+    let mut severe: Vec<usize> = records
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.completion_delay_days.is_some_and(|d| d as f64 > threshold))
+        .map(|(i, _)| i)
+        .collect();
+    severe.sort_by_key(|&i| Reverse(records[i].completion_delay_days));
+    severe
+}
+
+/// Finds records sharing the same province, contractor, type of work, start date,
+/// and approved budget. Returns (first occurrence, duplicate) index pairs.
+pub fn detect_duplicate_records(records: &[ProcessedRecord]) -> Vec<(usize, usize)> {
+    let mut first_seen: HashMap<(&str, &str, &str, Option<NaiveDate>, u64), usize> = HashMap::new();
+    let mut pairs = Vec::new();
+    for (i, r) in records.iter().enumerate() {
+        let key = (
+            r.province.as_str(),
+            r.contractor.as_str(),
+            r.type_of_work.as_str(),
+            r.start_date,
+            r.approved_budget_for_contract.to_bits(),
+        );
+        match first_seen.get(&key) {
+            Some(&first) => pairs.push((first, i)),
+            None => {
+                first_seen.insert(key, i);
+            }
+        }
+    }
+    pairs
+}
+
+/// Groups records by their exact coordinates and returns every pair used by more than one province.
+/// Imputed coordinates are skipped since they are derived from the province itself.
+/// Conflicts are ordered by latitude, then longitude.
+pub fn detect_coordinate_conflicts(records: &[ProcessedRecord]) -> Vec<CoordinateConflict> {
+    let mut by_coordinate: HashMap<(u64, u64), HashMap<&str, usize>> = HashMap::new();
+    for r in records.iter().filter(|r| !r.coordinates_imputed) {
+        if let (Some(lat), Some(lon)) = (r.project_latitude, r.project_longitude) {
+            *by_coordinate.entry((lat.to_bits(), lon.to_bits())).or_default().entry(r.province.as_str()).or_default() += 1;
+        }
+    }
+
+    let mut conflicts: Vec<CoordinateConflict> = by_coordinate
+        .into_iter()
+        .filter(|(_, provinces)| provinces.len() > 1)
+        .map(|((lat, lon), provinces)| {
+            let mut provinces: Vec<(String, usize)> =
+                provinces.into_iter().map(|(name, count)| (name.to_string(), count)).collect();
+            provinces.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            CoordinateConflict { latitude: f64::from_bits(lat), longitude: f64::from_bits(lon), provinces }
+        })
+        .collect();
+    conflicts.sort_by(|a, b| a.latitude.total_cmp(&b.latitude).then(a.longitude.total_cmp(&b.longitude)));
+    conflicts
+}
+
+/// Collapses projects that appear in more than one input file, as overlapping quarterly
+/// exports do. Records match on region, province, contractor, type of work, funding year
+/// and approved budget. The winner is the record with a completion date, then the one
+/// from the latest file in `file_order`, then the later row. Matching records from other
+/// files are dropped; same-file matches are left to `remove_duplicates`.
+/// Returns the kept records (in input order) and the collapsed ones.
+pub fn reconcile_cross_file_duplicates(
+    records: Vec<ProcessedRecord>,
+    file_order: &[String],
+) -> (Vec<ProcessedRecord>, Vec<ProcessedRecord>) {
+    // Region, province, contractor, type of work, funding year, budget bits.
+    type ProjectKey<'a> = (&'a str, &'a str, &'a str, &'a str, i32, u64);
+
+    let file_rank = |r: &ProcessedRecord| file_order.iter().position(|f| *f == r.source_file);
+    let mut groups: HashMap<ProjectKey, Vec<usize>> = HashMap::new();
+    for (i, r) in records.iter().enumerate() {
+        let key = (
+            r.region.as_str(),
+            r.province.as_str(),
+            r.contractor.as_str(),
+            r.type_of_work.as_str(),
+            r.funding_year,
+            r.approved_budget_for_contract.to_bits(),
+        );
+        groups.entry(key).or_default().push(i);
+    }
+
+    let mut collapsed_indices: HashSet<usize> = HashSet::new();
+    for indices in groups.values() {
+        let Some(&winner) = indices
+            .iter()
+            .max_by_key(|&&i| (records[i].actual_completion_date.is_some(), file_rank(&records[i]), i))
+        else {
+            continue;
+        };
+        let winning_file = &records[winner].source_file;
+        collapsed_indices.extend(indices.iter().filter(|&&i| records[i].source_file != *winning_file));
+    }
+
+    let mut kept = Vec::new();
+    let mut collapsed = Vec::new();
+    for (i, record) in records.into_iter().enumerate() {
+        if collapsed_indices.contains(&i) {
+            collapsed.push(record);
+        } else {
+            kept.push(record);
+        }
+    }
+    (kept, collapsed)
+}
+
+/// Keeps only the first occurrence of each duplicate group.
+/// Returns the kept records and the removed duplicates.
+pub fn remove_duplicates(records: Vec<ProcessedRecord>) -> (Vec<ProcessedRecord>, Vec<ProcessedRecord>) {
+    let duplicate_indices: HashSet<usize> = detect_duplicate_records(&records)
+        .into_iter()
+        .map(|(_, dup)| dup)
+        .collect();
+    let mut kept = Vec::new();
+    let mut removed = Vec::new();
+    for (i, record) in records.into_iter().enumerate() {
+        if duplicate_indices.contains(&i) {
+            removed.push(record);
+        } else {
+            kept.push(record);
+        }
+    }
+    (kept, removed)
+}
+
+/// Fills in missing latitude/longitude values using province averages.
+pub fn impute_coordinates(mut records: Vec<ProcessedRecord>) -> Vec<ProcessedRecord> {
+    // Group all known coordinates by province
+    let mut province_coords: HashMap<String, (Vec<f64>, Vec<f64>)> = HashMap::new();
+    for record in &records {
+        if record.province.is_empty() {
+            continue;
+        }
+        let entry = province_coords
+            .entry(record.province.clone())
+            .or_insert((Vec::new(), Vec::new()));
+        if let Some(lat) = record.project_latitude {
+            entry.0.push(lat);
+        }
+        if let Some(lng) = record.project_longitude {
+            entry.1.push(lng);
+        }
+    }
+
+    // Compute average coordinates per province
+    let mut province_averages: HashMap<String, (Option<f64>, Option<f64>)> = HashMap::new();
+    for (province, (lats, lngs)) in province_coords {
+        let avg_lat = if !lats.is_empty() {
+            Some(lats.iter().sum::<f64>() / lats.len() as f64)
+        } else {
+            None
+        };
+        let avg_lng = if !lngs.is_empty() {
+            Some(lngs.iter().sum::<f64>() / lngs.len() as f64)
+        } else {
+            None
+        };
+        province_averages.insert(province, (avg_lat, avg_lng));
+    }
+
+    // Impute missing coordinates with the computed averages
+    for record in &mut records {
+        if (record.project_latitude.is_none() || record.project_longitude.is_none())
+            && let Some((avg_lat, avg_lng)) = province_averages.get(&record.province)
+        {
+            if record.project_latitude.is_none() && avg_lat.is_some() {
+                record.project_latitude = *avg_lat;
+                record.coordinates_imputed = true;
+            }
+            if record.project_longitude.is_none() && avg_lng.is_some() {
+                record.project_longitude = *avg_lng;
+                record.coordinates_imputed = true;
+            }
+        }
+    }
+    records
+}
+
+/// Keeps only the records that satisfy every filter (logical AND).
+pub fn apply_filters(records: &[ProcessedRecord], filters: &[RecordFilter]) -> Vec<ProcessedRecord> {
+    records
+        .iter()
+        .filter(|r| filters.iter().all(|f| f.matches(r)))
+        .cloned()
+        .collect()
+}
+
+/// Finds records whose contractor name contains `keyword`, ignoring case.
+pub fn search_contractor_records(records: &[ProcessedRecord], keyword: &str) -> Vec<ProcessedRecord> {
+    let needle = keyword.to_lowercase();
+    records
+        .iter()
+        .filter(|r| r.contractor.to_lowercase().contains(&needle))
+        .cloned()
+        .collect()
+}
+
+/// Distinct contractor names matching `name`, ignoring case and sorted.
+/// An exact match is returned on its own; otherwise every name containing `name`.
+pub fn find_contractors(records: &[ProcessedRecord], name: &str) -> Vec<String> {
+    let needle = name.trim().to_lowercase();
+    if let Some(exact) = records.iter().find(|r| r.contractor.to_lowercase() == needle) {
+        return vec![exact.contractor.clone()];
+    }
+    let names: HashSet<&str> = records
+        .iter()
+        .filter(|r| r.contractor.to_lowercase().contains(&needle))
+        .map(|r| r.contractor.as_str())
+        .collect();
+    let mut names: Vec<String> = names.into_iter().map(str::to_string).collect();
+    names.sort();
+    names
+}
+
+/// Filters a vector of `ProcessedRecord`s to only include records whose
+/// `funding_year` is between `start_year` and `end_year` (inclusive).
+pub fn filter_by_year_range(records: Vec<ProcessedRecord>, start_year: i32, end_year: i32) -> Vec<ProcessedRecord> {
+    records
+        .into_iter()
+        .filter(|r| r.funding_year >= start_year && r.funding_year <= end_year)
+        .collect()
+}
@@ -0,0 +1,211 @@
+//! Validation and cleaning of raw CSV records.
+
+use chrono::prelude::*;
+
+use crate::model::{RawRecord, CleanedRecord, IslandWarning, ValidationLog, ValidationResult};
+use crate::config::{MIN_CONTRACT_VALUE, ValidationConfig};
+
+// ============================================================================
+// UTILITY FUNCTIONS - VALIDATION
+// ============================================================================
+
+// The island group each administrative region belongs to.
+pub static REGION_TO_ISLAND: &[(&str, &str)] = &[
+    ("National Capital Region", "Luzon"),
+    ("Cordillera Administrative Region", "Luzon"),
+    ("Region I", "Luzon"),
+    ("Region II", "Luzon"),
+    ("Region III", "Luzon"),
+    ("Region IV-A", "Luzon"),
+    ("Region IV-B", "Luzon"),
+    ("Region V", "Luzon"),
+    ("Region VI", "Visayas"),
+    ("Region VII", "Visayas"),
+    ("Region VIII", "Visayas"),
+    ("Negros Island Region", "Visayas"),
+    ("Region IX", "Mindanao"),
+    ("Region X", "Mindanao"),
+    ("Region XI", "Mindanao"),
+    ("Region XII", "Mindanao"),
+    ("Region XIII", "Mindanao"),
+    ("Bangsamoro Autonomous Region in Muslim Mindanao", "Mindanao"),
+];
+
+/// Validates and parses date strings safely, trying each format in order
+/// and returning the first successful parse.
+pub fn validate_date_flexible(date_str: &str, formats: &[String]) -> Option<NaiveDate> {
+    let date_str = date_str.trim();
+    if date_str.is_empty() || date_str == "N/A" {
+        return None;
+    }
+    formats
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(date_str, format).ok())
+}
+
+/// Converts strings to floating-point values if valid.
+pub fn validate_number(value: &str) -> Option<f64> {
+    if value.trim().is_empty() || value == "N/A" {
+        return None;
+    }
+    let cleaned = value.replace(',', "").trim().to_string();
+    cleaned.parse::<f64>().ok()
+}
+
+/// Like `validate_number`, but also rejects values outside `[min, max]`.
+pub fn validate_number_range(value: &str, min: f64, max: f64) -> Option<f64> {
+    validate_number(value).filter(|n| (min..=max).contains(n))
+}
+
+/// Checks whether a year is within the configured valid range.
+pub fn is_valid_year(year: i32, rules: &ValidationConfig) -> bool {
+    (rules.min_year..=rules.max_year).contains(&year)
+}
+
+/// The island group `region` belongs to, if it is a known region (any case).
+pub fn expected_island(region: &str) -> Option<&'static str> {
+    let region = region.trim();
+    REGION_TO_ISLAND
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(region))
+        .map(|&(_, island)| island)
+}
+
+/// Whether `island` is the island group of `region`.
+/// Unknown regions pass, since there is nothing to check them against.
+pub fn validate_island_consistency(region: &str, island: &str) -> bool {
+    expected_island(region).is_none_or(|expected| expected.eq_ignore_ascii_case(island.trim()))
+}
+
+/// Validates each raw record, checking required fields and data types.
+pub fn validate_record(record: &RawRecord, rules: &ValidationConfig) -> ValidationResult {
+    let mut errors = Vec::new();
+    
+    for field in &rules.required_fields {
+        if record.field(field).is_none_or(|v| v.trim().is_empty()) {
+            errors.push(format!("Missing {}", field));
+        }
+    }
+    
+    let year = record.funding_year.parse::<i32>().ok();
+    if !year.is_some_and(|y| is_valid_year(y, rules)) {
+        errors.push(format!("Invalid FundingYear: {}", record.funding_year));
+    }
+    
+    // Budgets and costs must be positive and below the configured ceiling.
+    for (name, value) in [
+        ("ApprovedBudgetForContract", &record.approved_budget_for_contract),
+        ("ContractCost", &record.contract_cost),
+    ] {
+        if value.trim().is_empty() {
+            continue;
+        }
+        match validate_number(value) {
+            None => errors.push(format!("Invalid {}: {}", name, value)),
+            Some(n) if validate_number_range(value, MIN_CONTRACT_VALUE, rules.max_contract_value).is_none() => {
+                errors.push(format!("{} out of range: {}", name, n))
+            }
+            Some(_) => {}
+        }
+    }
+
+    // Coordinates are optional, but present values must fall inside the configured ranges.
+    if let Some(lat) = validate_number(&record.project_latitude)
+        && !(rules.lat_range.0..=rules.lat_range.1).contains(&lat)
+    {
+        errors.push(format!("ProjectLatitude out of range: {}", lat));
+    }
+    if let Some(lon) = validate_number(&record.project_longitude)
+        && !(rules.lon_range.0..=rules.lon_range.1).contains(&lon)
+    {
+        errors.push(format!("ProjectLongitude out of range: {}", lon));
+    }
+
+    // A region filed under the wrong island skews island totals, but the row itself is usable.
+    let mut warnings = Vec::new();
+    if !validate_island_consistency(&record.region, &record.main_island)
+        && let Some(expected) = expected_island(&record.region)
+    {
+        warnings.push(format!("MainIsland {} does not match {} (expected {})", record.main_island, record.region, expected));
+    }
+    
+    ValidationResult {
+        is_valid: errors.is_empty(),
+        errors,
+        warnings,
+    }
+}
+
+/// Converts a valid RawRecord into a CleanedRecord with proper data types.
+pub fn clean_record(record: &RawRecord, rules: &ValidationConfig) -> Option<CleanedRecord> {
+    if !validate_record(record, rules).is_valid {
+        return None;
+    }
+    convert_record(record, rules)
+}
+
+/// Parses the fields of a record that has already passed validation.
+fn convert_record(record: &RawRecord, rules: &ValidationConfig) -> Option<CleanedRecord> {
+    let approved_budget =
+        validate_number_range(&record.approved_budget_for_contract, MIN_CONTRACT_VALUE, rules.max_contract_value)?;
+    let contract_cost = validate_number_range(&record.contract_cost, MIN_CONTRACT_VALUE, rules.max_contract_value)?;
+    let start_date = validate_date_flexible(&record.start_date, &rules.date_formats);
+    let actual_completion_date = validate_date_flexible(&record.actual_completion_date, &rules.date_formats);
+    let latitude = validate_number(&record.project_latitude);
+    let longitude = validate_number(&record.project_longitude);
+    let funding_year = record.funding_year.parse::<i32>().ok()?;
+
+    Some(CleanedRecord {
+        region: record.region.clone(),
+        main_island: record.main_island.clone(),
+        funding_year,
+        approved_budget_for_contract: approved_budget,
+        contract_cost,
+        start_date,
+        actual_completion_date,
+        project_latitude: latitude,
+        project_longitude: longitude,
+        province: record.province.clone(),
+        contractor: if record.contractor.trim().is_empty() {
+            "Unknown".to_string()
+        } else {
+            record.contractor.clone()
+        },
+        type_of_work: if record.type_of_work.trim().is_empty() {
+            "Unknown".to_string()
+        } else {
+            record.type_of_work.clone()
+        },
+        source_file: record.source_file.clone(),
+    })
+}
+
+/// Validates and cleans one raw record.
+/// Rejected rows produce an error message pointing at their file and line;
+/// kept rows whose island does not match their region are logged as warnings.
+pub fn clean_raw_record(
+    record: &RawRecord,
+    row_in_file: usize,
+    rules: &ValidationConfig,
+    log: &mut ValidationLog,
+) -> Option<CleanedRecord> {
+    let validation = validate_record(record, rules);
+    if !validation.is_valid {
+        // Store validation error messages for invalid rows (+1 for the header line).
+        log.errors.push(format!("{}, Row {}:\n{}", record.source_file, row_in_file + 1, validation));
+        return None;
+    }
+    let clean = convert_record(record, rules)?;
+    if !validation.warnings.is_empty()
+        && let Some(expected) = expected_island(&record.region)
+    {
+        log.island_warnings.push(IslandWarning {
+            source_file: record.source_file.clone(),
+            row: row_in_file + 1,
+            region: record.region.clone(),
+            main_island: record.main_island.clone(),
+            expected_island: expected.to_string(),
+        });
+    }
+    Some(clean)
+}
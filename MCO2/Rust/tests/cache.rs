@@ -0,0 +1,51 @@
+mod common;
+
+use std::fs;
+
+use dpwh::cache::{cache_key, read_cache, write_cache, CacheKey};
+use dpwh::config::Settings;
+
+use common::project;
+
+#[test]
+fn cache_round_trip_reproduces_records() {
+    let mut imputed = project("Region II", 10.0, 12.0).province("Cagayan").coords(Some(17.6), Some(121.7)).build();
+    imputed.coordinates_imputed = true;
+    let records = vec![
+        project("Region I", 1_000.5, 900.25).delay(45).file("q2.csv").build(),
+        imputed,
+    ];
+    let key = CacheKey { source_hash: 1, settings_hash: 2 };
+
+    let path = std::env::temp_dir().join(format!("dpwh_cache_{}", std::process::id())).join("processed.bin");
+    write_cache(&path, key, &records).unwrap();
+    let restored = read_cache(&path, key);
+    let stale_source = read_cache(&path, CacheKey { source_hash: 9, ..key });
+    let stale_settings = read_cache(&path, CacheKey { settings_hash: 9, ..key });
+    fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert_eq!(restored, Some(records));
+    assert_eq!(stale_source, None);
+    assert_eq!(stale_settings, None);
+}
+
+#[test]
+fn cache_key_follows_file_contents_and_loading_options() {
+    let path = std::env::temp_dir().join(format!("dpwh_cache_source_{}.csv", std::process::id()));
+    let files = [path.clone()];
+    let settings = Settings::default();
+
+    fs::write(&path, "a,b\n1,2\n").unwrap();
+    let original = cache_key(&files, &settings).unwrap();
+    let unchanged = cache_key(&files, &settings).unwrap();
+    fs::write(&path, "a,b\n1,3\n").unwrap();
+    let edited = cache_key(&files, &settings).unwrap();
+    let keep_duplicates = Settings { remove_duplicates: false, ..Settings::default() };
+    let other_options = cache_key(&files, &keep_duplicates).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(original, unchanged);
+    assert_ne!(edited.source_hash, original.source_hash);
+    assert_eq!(edited.settings_hash, original.settings_hash);
+    assert_ne!(other_options.settings_hash, edited.settings_hash);
+}
@@ -0,0 +1,111 @@
+// Small fixture builders shared by the integration tests.
+// Each test binary uses a different subset of them.
+#![allow(dead_code)]
+
+use chrono::NaiveDate;
+
+use dpwh::model::{CleanedRecord, ProcessedRecord, RawRecord};
+use dpwh::transform::add_derived_fields;
+
+// A raw CSV row that passes every default validation rule.
+pub fn raw_record() -> RawRecord {
+    RawRecord {
+        region: "Region I".to_string(),
+        main_island: "Luzon".to_string(),
+        funding_year: "2022".to_string(),
+        approved_budget_for_contract: "1,000,000.00".to_string(),
+        contract_cost: "950000".to_string(),
+        start_date: "2022-01-10".to_string(),
+        actual_completion_date: "2022-03-11".to_string(),
+        project_latitude: "16.5".to_string(),
+        project_longitude: "120.3".to_string(),
+        province: "La Union".to_string(),
+        contractor: "ACME BUILDERS".to_string(),
+        type_of_work: "Construction of Flood Mitigation Structure".to_string(),
+        source_file: "fixture.csv".to_string(),
+    }
+}
+
+pub fn date(text: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+}
+
+// Builder for processed records; only the fields a test cares about need setting.
+pub struct Fixture {
+    record: CleanedRecord,
+}
+
+pub fn project(region: &str, budget: f64, cost: f64) -> Fixture {
+    Fixture {
+        record: CleanedRecord {
+            region: region.to_string(),
+            main_island: "Luzon".to_string(),
+            funding_year: 2021,
+            approved_budget_for_contract: budget,
+            contract_cost: cost,
+            start_date: None,
+            actual_completion_date: None,
+            project_latitude: None,
+            project_longitude: None,
+            province: "Province".to_string(),
+            contractor: "Contractor".to_string(),
+            type_of_work: "Dike".to_string(),
+            source_file: "fixture.csv".to_string(),
+        },
+    }
+}
+
+impl Fixture {
+    pub fn island(mut self, island: &str) -> Self {
+        self.record.main_island = island.to_string();
+        self
+    }
+
+    pub fn year(mut self, year: i32) -> Self {
+        self.record.funding_year = year;
+        self
+    }
+
+    pub fn contractor(mut self, contractor: &str) -> Self {
+        self.record.contractor = contractor.to_string();
+        self
+    }
+
+    pub fn work(mut self, type_of_work: &str) -> Self {
+        self.record.type_of_work = type_of_work.to_string();
+        self
+    }
+
+    pub fn province(mut self, province: &str) -> Self {
+        self.record.province = province.to_string();
+        self
+    }
+
+    // Starts on 2021-01-01 and finishes `days` later.
+    pub fn delay(mut self, days: i64) -> Self {
+        let start = date("2021-01-01");
+        self.record.start_date = Some(start);
+        self.record.actual_completion_date = Some(start + chrono::Duration::days(days));
+        self
+    }
+
+    pub fn file(mut self, source_file: &str) -> Self {
+        self.record.source_file = source_file.to_string();
+        self
+    }
+
+    pub fn coords(mut self, lat: Option<f64>, lon: Option<f64>) -> Self {
+        self.record.project_latitude = lat;
+        self.record.project_longitude = lon;
+        self
+    }
+
+    // The record as it leaves cleaning, before derived fields are added.
+    pub fn cleaned(self) -> CleanedRecord {
+        self.record
+    }
+
+    pub fn build(self) -> ProcessedRecord {
+        add_derived_fields(self.record)
+    }
+}
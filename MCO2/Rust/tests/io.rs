@@ -0,0 +1,301 @@
+mod common;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use dpwh::config::OverwriteGuard;
+use dpwh::error::PipelineError;
+use dpwh::io::{
+    check_writable, export_to_sqlite, read_and_merge_csvs, read_csv, resolve_input_files, rows_per_file, run_with_timeout, source_name, write_csv_to_writer, write_json_to_writer,
+    write_processed_json,
+};
+use dpwh::model::{Delimiter, ProcessedRecord, RawRecord, ReportRecord};
+use dpwh::reports::{generate_report6, write_report_to_writer, Report6Temp};
+
+use common::{date, project};
+
+// Writes `contents` to a file in the system temp directory, unique per test.
+fn temp_csv(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("dpwh_{}_{}.csv", name, std::process::id()));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn header_line() -> String {
+    RawRecord::HEADERS.join(",")
+}
+
+#[test]
+fn reads_rows_under_the_expected_headers() {
+    let row = "Region I,Luzon,2022,100,90,2022-01-01,2022-02-01,16.5,120.3,La Union,ACME,Dike";
+    let path = temp_csv("valid", &format!("{}\n{}\n", header_line(), row));
+    let records = read_csv(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].province, "La Union");
+}
+
+#[test]
+fn a_utf8_bom_does_not_hide_the_first_header() {
+    let row = "Region I,Luzon,2022,100,90,2022-01-01,2022-02-01,16.5,120.3,La Union,ACME,Dike";
+    let path = temp_csv("bom", &format!("\u{feff}{}\n{}\n", header_line(), row));
+    let records = read_csv(&path).unwrap();
+    let merged = read_and_merge_csvs(std::slice::from_ref(&path)).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(records[0].region, "Region I");
+    assert_eq!(merged[0].region, "Region I");
+}
+
+#[test]
+fn missing_columns_are_named() {
+    let headers = header_line().replace(",Contractor", "").replace("Region,", "");
+    let path = temp_csv("missing", &format!("{}\n", headers));
+    let err = read_csv(&path).err().expect("reading should fail");
+    fs::remove_file(&path).unwrap();
+    match &err {
+        PipelineError::MissingHeaders { missing, .. } => assert_eq!(missing, &["Region", "Contractor"]),
+        other => panic!("expected MissingHeaders, got {:?}", other),
+    }
+    assert_eq!(err.exit_code(), 65);
+}
+
+#[test]
+fn parse_errors_carry_file_and_line() {
+    let good = "Region I,Luzon,2022,100,90,,,,,La Union,ACME,Dike";
+    let short = "Region I,Luzon,2022";
+    let path = temp_csv("short_row", &format!("{}\n{}\n{}\n", header_line(), good, short));
+    let err = read_csv(&path).err().expect("reading should fail");
+    fs::remove_file(&path).unwrap();
+    match &err {
+        PipelineError::CsvParse { file, row, .. } => {
+            assert_eq!(file, &path.display().to_string());
+            assert_eq!(*row, 3);
+        }
+        other => panic!("expected CsvParse, got {:?}", other),
+    }
+    assert!(err.to_string().contains(", line 3: "));
+}
+
+#[test]
+fn only_empty_data_errors_are_recoverable() {
+    assert!(PipelineError::NoDataLoaded.is_recoverable());
+    assert!(PipelineError::NoValidRecords { total: 4 }.is_recoverable());
+    assert!(!PipelineError::DataFileNotFound { searched: Vec::new() }.is_recoverable());
+    assert_eq!(
+        PipelineError::NoValidRecords { total: 4 }.to_string(),
+        "No valid records to process (all 4 rows failed validation)"
+    );
+}
+
+#[test]
+fn batch_runs_refuse_to_replace_existing_files() {
+    let existing = temp_csv("existing", "kept");
+    let fresh = existing.with_extension("new");
+    let guard = OverwriteGuard::default();
+    assert!(guard.confirm(&fresh).unwrap());
+    let err = guard.confirm_all(&[fresh, existing.clone()]).expect_err("existing file should be refused");
+    let forced = OverwriteGuard { force: true, ..OverwriteGuard::default() }.confirm(&existing).unwrap();
+    fs::remove_file(&existing).unwrap();
+
+    match &err {
+        PipelineError::OutputExists { paths } => assert_eq!(paths, &[existing]),
+        other => panic!("expected OutputExists, got {:?}", other),
+    }
+    assert_eq!(err.exit_code(), 73);
+    assert!(forced);
+}
+
+#[test]
+fn writable_check_creates_the_directory_and_leaves_no_probe() {
+    let dir = std::env::temp_dir().join(format!("dpwh_writable_{}", std::process::id())).join("reports");
+    check_writable(&dir).unwrap();
+    let leftovers = fs::read_dir(&dir).unwrap().count();
+    fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    assert_eq!(leftovers, 0);
+
+    // A directory cannot be created under a regular file, even when running as root.
+    let blocker = temp_csv("not_a_dir", "");
+    let blocked = check_writable(&blocker.join("reports"));
+    fs::remove_file(&blocker).unwrap();
+    assert!(blocked.is_err());
+}
+
+#[test]
+fn input_globs_and_directories_merge_their_files() {
+    let dir = std::env::temp_dir().join(format!("dpwh_inputs_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let row = "Region I,Luzon,2022,100,90,,,,,La Union,ACME,Dike";
+    fs::write(dir.join("north.csv"), format!("{}\n{}\n{}\n", header_line(), row, row)).unwrap();
+    fs::write(dir.join("south.csv"), format!("{}\n{}\n", header_line(), row)).unwrap();
+    fs::write(dir.join("notes.txt"), "not a dataset").unwrap();
+
+    let pattern = dir.join("*.csv").display().to_string();
+    let from_glob = resolve_input_files(&[pattern]).unwrap();
+    let from_dir = resolve_input_files(&[dir.display().to_string(), dir.join("north.csv").display().to_string()]).unwrap();
+    let records = read_and_merge_csvs(&from_glob).unwrap();
+    let file_rows = rows_per_file(&records, &from_glob);
+    let missing = resolve_input_files(&[dir.join("*.tsv").display().to_string()]).err();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(from_glob, vec![dir.join("north.csv"), dir.join("south.csv")]);
+    assert_eq!(from_dir, from_glob);
+    assert_eq!(file_rows, vec![2, 1]);
+    assert_eq!(records[2].source_file, source_name(&dir.join("south.csv")));
+    assert!(matches!(missing, Some(PipelineError::InputNotFound { .. })));
+}
+
+#[test]
+fn same_named_files_in_different_folders_stay_apart() {
+    let dir = std::env::temp_dir().join(format!("dpwh_quarters_{}", std::process::id()));
+    let (q2, q4) = (dir.join("q2").join("data.csv"), dir.join("q4").join("data.csv"));
+    let row = "Region I,Luzon,2022,100,90,,,,,La Union,ACME,Dike";
+    for (path, rows) in [(&q2, 2), (&q4, 1)] {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, format!("{}\n{}", header_line(), format!("{}\n", row).repeat(rows))).unwrap();
+    }
+
+    let files = vec![q2.clone(), q4.clone()];
+    let records = read_and_merge_csvs(&files).unwrap();
+    let file_rows = rows_per_file(&records, &files);
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_ne!(source_name(&q2), source_name(&q4));
+    assert_eq!(file_rows, vec![2, 1]);
+    assert_eq!(records[2].source_file, source_name(&q4));
+}
+
+#[test]
+fn processed_json_round_trips_every_field() {
+    let finished = project("Region I", 1_000.5, 900.25).delay(45).coords(Some(16.5), Some(120.3)).build();
+    let mut imputed = project("Region II", 10.0, 12.0).province("Cagayan").coords(Some(17.6), Some(121.7)).build();
+    imputed.coordinates_imputed = true;
+    let records = vec![finished, imputed];
+
+    let path = std::env::temp_dir().join(format!("dpwh_processed_{}.json", std::process::id()));
+    write_processed_json(&path, &records).unwrap();
+    let text = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert!(text.contains("\"start_date\": \"2021-01-01\""));
+    assert!(text.contains("\"actual_completion_date\": null"));
+    let restored: Vec<ProcessedRecord> = serde_json::from_str(&text).unwrap();
+    assert_eq!(restored, records);
+}
+
+#[test]
+fn csv_writer_fills_missing_cells_and_escapes_values() {
+    let row = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    };
+    let data = vec![
+        row(&[("Contractor", "ACME, Inc."), ("Note", "said \"fast\"")]),
+        row(&[("Contractor", "Plain")]),
+    ];
+    let mut buffer = Vec::new();
+    write_csv_to_writer(&mut buffer, &data, &["Contractor", "Note"], Delimiter::Comma, false).unwrap();
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "Contractor,Note\n\"ACME, Inc.\",\"said \"\"fast\"\"\"\nPlain,\n"
+    );
+}
+
+#[test]
+fn excel_compatible_reports_round_trip_through_the_csv_reader() {
+    let records = vec![
+        project("R", 300.0, 250.0).island("Luzon").build(),
+        project("R", 100.0, 80.0).island("Mindanao, Sulu").build(),
+    ];
+    let rows = generate_report6(&records, 0);
+
+    let mut buffer = Vec::new();
+    write_report_to_writer(&mut buffer, &rows, &[], Delimiter::Comma, true).unwrap();
+    assert!(buffer.starts_with(b"\xEF\xBB\xBF"));
+    assert_eq!(buffer.windows(3).filter(|w| *w == b"\xEF\xBB\xBF").count(), 1);
+    let text = std::str::from_utf8(&buffer[3..]).unwrap();
+    assert_eq!(text.matches("\r\n").count(), 3);
+    assert_eq!(text.matches('\n').count(), 3);
+
+    let mut reader = csv::Reader::from_reader(&buffer[3..]);
+    assert_eq!(reader.headers().unwrap().iter().collect::<Vec<_>>(), Report6Temp::HEADERS);
+    let islands: Vec<String> = reader.records().map(|r| r.unwrap()[0].to_string()).collect();
+    assert_eq!(islands, vec!["Luzon", "Mindanao, Sulu"]);
+
+    let mut buffer = Vec::new();
+    write_report_to_writer(&mut buffer, &rows, &[], Delimiter::Comma, false).unwrap();
+    assert!(buffer.starts_with(b"MainIsland,"));
+    let text = String::from_utf8(buffer).unwrap();
+    assert!(!text.contains('\r'));
+    assert_eq!(text.matches('\n').count(), 3);
+}
+
+#[test]
+fn json_writer_pretty_prints() {
+    let mut buffer = Vec::new();
+    write_json_to_writer(&mut buffer, &serde_json::json!({ "total": 2 })).unwrap();
+    assert_eq!(String::from_utf8(buffer).unwrap(), "{\n  \"total\": 2\n}");
+}
+
+#[test]
+fn slow_loads_time_out() {
+    let limit = Duration::from_millis(20);
+    let slow = run_with_timeout(Some(limit), || {
+        std::thread::sleep(Duration::from_millis(500));
+        Ok(1)
+    });
+    match slow {
+        Err(PipelineError::Timeout(waited)) => assert_eq!(waited, limit),
+        other => panic!("expected Timeout, got {:?}", other),
+    }
+    assert_eq!(PipelineError::Timeout(limit).exit_code(), 75);
+
+    assert_eq!(run_with_timeout(Some(Duration::from_secs(5)), || Ok(2)).unwrap(), 2);
+    assert_eq!(run_with_timeout(None, || Ok(3)).unwrap(), 3);
+}
+
+#[test]
+fn sqlite_export_round_trips_every_field() {
+    let mut imputed = project("Region II", 300.0, 310.0).island("Visayas").year(2023).work("Seawall").coords(Some(10.3), Some(123.9)).file("b.csv").build();
+    imputed.coordinates_imputed = true;
+    let records = vec![
+        project("Region I", 100.0, 90.0).delay(30).province("La Union").contractor("ACME").coords(Some(16.5), Some(120.3)).file("a.csv").build(),
+        imputed,
+        project("Region III", 50.0, 50.0).year(2022).build(),
+    ];
+    let path = std::env::temp_dir().join(format!("dpwh_export_{}.db", std::process::id()));
+
+    assert_eq!(export_to_sqlite(&records, &path).unwrap(), 3);
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let mut stmt = conn.prepare("SELECT * FROM flood_control_projects").unwrap();
+    let read: Vec<ProcessedRecord> = stmt
+        .query_map([], |row| {
+            let day = |text: Option<String>| text.map(|t| date(&t));
+            Ok(ProcessedRecord {
+                region: row.get(0)?,
+                main_island: row.get(1)?,
+                funding_year: row.get(2)?,
+                approved_budget_for_contract: row.get(3)?,
+                contract_cost: row.get(4)?,
+                start_date: day(row.get(5)?),
+                actual_completion_date: day(row.get(6)?),
+                project_latitude: row.get(7)?,
+                project_longitude: row.get(8)?,
+                province: row.get(9)?,
+                contractor: row.get(10)?,
+                type_of_work: row.get(11)?,
+                cost_savings: row.get(12)?,
+                completion_delay_days: row.get(13)?,
+                coordinates_imputed: row.get(14)?,
+                source_file: row.get(15)?,
+            })
+        })
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    drop(stmt);
+    drop(conn);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(read, records);
+}
@@ -0,0 +1,50 @@
+use std::fs;
+
+use dpwh::manifest::{hash_file, read_manifest, verify_manifest, write_manifest, ManifestProblem, MANIFEST_FILE};
+
+#[test]
+fn sha256_matches_the_known_digest() {
+    let path = std::env::temp_dir().join(format!("dpwh_hash_{}.txt", std::process::id()));
+    fs::write(&path, "abc").unwrap();
+    let (sha256, bytes) = hash_file(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(sha256, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    assert_eq!(bytes, 3);
+}
+
+#[test]
+fn manifest_lists_files_and_catches_changes() {
+    let dir = std::env::temp_dir().join(format!("dpwh_manifest_{}", std::process::id()));
+    fs::create_dir_all(dir.join(".cache")).unwrap();
+    fs::write(dir.join("report.csv"), "Region,Total\n\"Region I, North\",1\nTOTAL,1\n").unwrap();
+    fs::write(dir.join("report.json"), "[{\"a\": 1}, {\"a\": 2}, {\"a\": 3}]").unwrap();
+    fs::write(dir.join("report.html"), "<table></table>").unwrap();
+    fs::write(dir.join(".cache").join("processed.bin"), "cache").unwrap();
+    // Left behind by an earlier run; this run did not write it.
+    fs::write(dir.join("report9.csv"), "FundingYear\nTOTAL\n").unwrap();
+
+    let files: Vec<_> = ["report.json", "report.csv", "report.html", "report.csv", MANIFEST_FILE].iter().map(|f| dir.join(f)).collect();
+    write_manifest(&dir, &files).unwrap();
+    let manifest = read_manifest(&dir).unwrap();
+    let listed: Vec<(&str, Option<usize>)> = manifest.files.iter().map(|f| (f.file.as_str(), f.rows)).collect();
+    let untouched = verify_manifest(&dir, &manifest).unwrap();
+
+    // Same size, different bytes: only the hash can tell.
+    fs::write(dir.join("report.csv"), "Region,Total\n\"Region I, North\",2\nTOTAL,2\n").unwrap();
+    fs::remove_file(dir.join("report.html")).unwrap();
+    fs::write(dir.join("report.json"), "[]").unwrap();
+    let changed = verify_manifest(&dir, &manifest).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(listed, vec![("report.csv", Some(2)), ("report.html", None), ("report.json", Some(3))]);
+    assert!(!listed.iter().any(|(file, _)| *file == MANIFEST_FILE));
+    assert!(untouched.is_empty());
+    assert_eq!(
+        changed,
+        vec![
+            ("report.csv".to_string(), ManifestProblem::HashChanged),
+            ("report.html".to_string(), ManifestProblem::Missing),
+            ("report.json".to_string(), ManifestProblem::SizeChanged { expected: 30, actual: 2 }),
+        ]
+    );
+}
@@ -0,0 +1,759 @@
+mod common;
+
+use std::fs;
+
+use dpwh::compute::{
+    calculate_cagr, calculate_coefficient_of_variation, calculate_cumulative_budget, calculate_cumulative_savings, calculate_gini, compute_regional_budget_cagr, calculate_median, calculate_moving_average, calculate_percentile, calculate_std_dev, calculate_weighted_average, format_large_number, format_number,
+    format_php_amount, group_by, sort_by_multiple_keys, top_n, OrdF64,
+};
+use dpwh::app::{report_outputs, Pipeline};
+use dpwh::config::{parse_sort_list, Config, OutputSettings, PreviewConfig, ReportSort};
+use dpwh::model::{Delimiter, OutputFormat, ProcessedRecord, ReportRecord};
+use dpwh::reports::{
+    build_total_row, generate_contractor_drilldown, generate_report1, generate_report2, generate_report3, generate_report6, generate_report8,
+    generate_report9, generate_summary, report_headers, sort_report_rows, apply_report_sort, write_html_page, write_report, write_report_to_writer, Report1Temp, Report2Temp, Report3Temp, Report6Temp, Report8Temp, Report9Temp,
+};
+
+use common::{date, project};
+
+// The formatted values of one column, in row order.
+fn column<R: ReportRecord>(rows: &[R], name: &str) -> Vec<String> {
+    rows.iter().map(|row| cell(row, name)).collect()
+}
+
+// The formatted value of one cell.
+fn cell<R: ReportRecord>(row: &R, name: &str) -> String {
+    row.cell(name).unwrap_or_else(|| panic!("no column {}", name))
+}
+
+#[test]
+fn formatting_rounds_and_blanks_non_finite_values() {
+    assert_eq!(format_number(2.345, 2), "2.35");
+    assert_eq!(format_number(f64::NAN, 2), "");
+    assert_eq!(format_large_number(1_234_567.89), "1234568");
+}
+
+#[test]
+fn php_amounts_are_rounded_and_grouped() {
+    assert_eq!(format_php_amount(1_234_567.89), "₱1,234,568");
+    assert_eq!(format_php_amount(5_000_000.0), "₱5,000,000");
+    assert_eq!(format_php_amount(999.0), "₱999");
+    assert_eq!(format_php_amount(-1_500.0), "-₱1,500");
+    assert_eq!(format_php_amount(-0.4), "₱0");
+    assert_eq!(format_php_amount(f64::INFINITY), "");
+}
+
+#[test]
+fn median_and_percentiles_interpolate() {
+    assert_eq!(calculate_median(&[]), 0.0);
+    assert_eq!(calculate_median(&[3.0, 1.0, 2.0]), 2.0);
+    assert_eq!(calculate_median(&[4.0, 1.0, 3.0, 2.0]), 2.5);
+    assert_eq!(calculate_percentile(&[10.0, 20.0, 30.0, 40.0, 50.0], 25.0), 20.0);
+    assert_eq!(calculate_percentile(&[1.0, 2.0, 3.0, 4.0], 50.0), calculate_median(&[1.0, 2.0, 3.0, 4.0]));
+}
+
+#[test]
+fn percentiles_of_edge_inputs_are_exact() {
+    assert_eq!(calculate_percentile(&[], 50.0), 0.0);
+    assert_eq!(calculate_percentile(&[7.0], 90.0), 7.0);
+    assert_eq!(calculate_median(&[7.0]), 7.0);
+    let values = [0.3, -12.5, 8.1, 4.0, 99.9];
+    assert_eq!(calculate_percentile(&values, 0.0), -12.5);
+    assert_eq!(calculate_percentile(&values, 100.0), 99.9);
+}
+
+#[test]
+fn std_dev_is_the_sample_spread() {
+    assert_eq!(calculate_std_dev(&[]), 0.0);
+    assert_eq!(calculate_std_dev(&[42.0]), 0.0);
+    // Squared deviations sum to 32 over 8 values: variance 32 / 7.
+    let std_dev = calculate_std_dev(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+    assert!((std_dev - (32.0_f64 / 7.0).sqrt()).abs() < 1e-12, "{}", std_dev);
+}
+
+#[test]
+fn std_dev_cells_of_tiny_groups_are_zero_not_nan() {
+    // One project without dates in the region, one project in the year/type.
+    let report1 = generate_report1(&[project("Region A", 100.0, 90.0).build()], 0);
+    assert_eq!(cell(&report1[0], "DelayStdDev"), "0.00");
+    let report3 = generate_report3(&[project("R", 100.0, 90.0).build()], 3);
+    assert_eq!(cell(&report3[0], "SavingsStdDev"), "0.00");
+}
+
+#[test]
+fn coefficient_of_variation_is_std_dev_over_mean() {
+    assert_eq!(calculate_coefficient_of_variation(&[7.0, 7.0, 7.0]), Some(0.0));
+    let cv = calculate_coefficient_of_variation(&[1.0, 2.0, 3.0, 4.0, 5.0]).unwrap();
+    assert!((cv - 2.5_f64.sqrt() / 3.0).abs() < 1e-12, "{}", cv);
+    assert_eq!(calculate_coefficient_of_variation(&[]), None);
+    assert_eq!(calculate_coefficient_of_variation(&[-1.0, 1.0]), None);
+}
+
+#[test]
+fn gini_is_zero_when_equal_and_near_one_when_concentrated() {
+    assert_eq!(calculate_gini(&[250.0; 8]), 0.0);
+
+    // One group holding everything scores (n - 1) / n.
+    let mut concentrated = vec![0.0; 999];
+    concentrated.push(1_000_000.0);
+    let gini = calculate_gini(&concentrated);
+    assert!((gini - 0.999).abs() < 1e-9, "{}", gini);
+
+    assert_eq!(calculate_gini(&[]), 0.0);
+    assert_eq!(calculate_gini(&[5.0]), 0.0);
+    assert_eq!(calculate_gini(&[0.0, 0.0]), 0.0);
+}
+
+#[test]
+fn cumulative_totals_run_over_three_years() {
+    // Listed out of year order: 2023 first, then 2021, then 2022.
+    let records = vec![
+        project("R", 400.0, 350.0).year(2023).build(),
+        project("R", 100.0, 90.0).year(2021).build(),
+        project("R", 200.0, 220.0).year(2022).build(),
+        project("R", 300.0, 250.0).year(2021).build(),
+    ];
+    // Budget per year: 2021 = 400, 2022 = 200, 2023 = 400.
+    assert_eq!(calculate_cumulative_budget(&records, true), vec![(2021, 400.0), (2022, 600.0), (2023, 1000.0)]);
+    // Savings per year: 2021 = 10 + 50, 2022 = -20, 2023 = 50.
+    assert_eq!(calculate_cumulative_savings(&records, true), vec![(2021, 60.0), (2022, 40.0), (2023, 90.0)]);
+    // Unsorted, years keep the order they first appear in.
+    assert_eq!(calculate_cumulative_budget(&records, false), vec![(2023, 400.0), (2021, 800.0), (2022, 1000.0)]);
+    assert!(calculate_cumulative_budget(&[], true).is_empty());
+}
+
+#[test]
+fn weighted_average_leans_toward_heavier_values() {
+    // (10 * 1 + 40 * 3) / 4
+    assert_eq!(calculate_weighted_average(&[10.0, 40.0], &[1.0, 3.0]), Some(32.5));
+    assert_eq!(calculate_weighted_average(&[10.0, 40.0], &[0.0, 0.0]), None);
+    assert_eq!(calculate_weighted_average(&[], &[]), None);
+    assert_eq!(calculate_weighted_average(&[10.0, 40.0], &[1.0]), None);
+}
+
+#[test]
+fn moving_average_waits_for_a_full_window() {
+    let values = [Some(1.0), Some(2.0), Some(6.0), Some(4.0)];
+    assert_eq!(calculate_moving_average(&values, 2), vec![None, Some(1.5), Some(4.0), Some(5.0)]);
+    assert_eq!(calculate_moving_average(&values, 3), vec![None, None, Some(3.0), Some(4.0)]);
+    assert_eq!(calculate_moving_average(&values, 5), vec![None; 4]);
+}
+
+#[test]
+fn moving_average_does_not_bridge_a_missing_value() {
+    let values = [Some(1.0), None, Some(6.0), Some(4.0)];
+    assert_eq!(calculate_moving_average(&values, 2), vec![None, None, None, Some(5.0)]);
+    assert_eq!(calculate_moving_average(&values, 1), vec![Some(1.0), None, Some(6.0), Some(4.0)]);
+}
+
+#[test]
+fn cagr_compounds_over_the_years() {
+    let cagr = calculate_cagr(100.0, 121.0, 2.0).unwrap();
+    assert!((cagr - 0.10).abs() < 1e-12);
+    assert!((calculate_cagr(200.0, 100.0, 1.0).unwrap() + 0.5).abs() < 1e-12);
+    assert_eq!(calculate_cagr(0.0, 121.0, 2.0), None);
+    assert_eq!(calculate_cagr(100.0, 121.0, 0.0), None);
+}
+
+#[test]
+fn regional_cagr_runs_from_the_first_to_the_last_funded_year() {
+    let records = vec![
+        project("Region A", 60.0, 60.0).year(2021).build(),
+        project("Region A", 40.0, 40.0).year(2021).build(),
+        project("Region A", 500.0, 500.0).year(2022).build(),
+        project("Region A", 121.0, 121.0).year(2023).build(),
+        project("Region B", 100.0, 100.0).year(2022).build(),
+    ];
+
+    let growth = compute_regional_budget_cagr(&records);
+    assert!((growth["Region A"] - 0.10).abs() < 1e-12);
+    assert!(!growth.contains_key("Region B"));
+
+    let rows = generate_report1(&records, 0);
+    let by_region = |region: &str| rows.iter().find(|r| r.region == region).unwrap();
+    assert_eq!(cell(by_region("Region A"), "RegionCagr"), "10.00");
+    assert_eq!(cell(by_region("Region B"), "RegionCagr"), "N/A");
+}
+
+#[test]
+fn group_by_buckets_records_and_keeps_input_order_within_groups() {
+    let records = vec![
+        project("Region I", 100.0, 90.0).year(2021).build(),
+        project("Region II", 200.0, 150.0).year(2021).work("Seawall").build(),
+        project("Region I", 300.0, 310.0).year(2022).build(),
+        project("Region I", 400.0, 380.0).year(2021).work("Seawall").build(),
+        project("Region III", 500.0, 450.0).year(2021).build(),
+    ];
+
+    let by_region = group_by(&records, |r| r.region.clone());
+    assert_eq!(by_region.len(), 3);
+    let budgets = |group: &[&ProcessedRecord]| -> Vec<f64> { group.iter().map(|r| r.approved_budget_for_contract).collect() };
+    assert_eq!(budgets(&by_region["Region I"]), vec![100.0, 300.0, 400.0]);
+    assert_eq!(by_region["Region II"].len(), 1);
+    assert_eq!(by_region["Region III"].len(), 1);
+
+    let by_year_and_type = group_by(&records, |r| (r.funding_year, r.type_of_work.clone()));
+    assert_eq!(by_year_and_type.len(), 3);
+    assert_eq!(budgets(&by_year_and_type[&(2021, "Dike".to_string())]), vec![100.0, 500.0]);
+    assert_eq!(budgets(&by_year_and_type[&(2021, "Seawall".to_string())]), vec![200.0, 400.0]);
+    assert_eq!(by_year_and_type[&(2022, "Dike".to_string())].len(), 1);
+}
+
+#[test]
+fn top_n_returns_largest_first_and_is_stable() {
+    let items = vec![("a", 3), ("b", 9), ("c", 3), ("d", 7), ("e", 9)];
+    assert_eq!(top_n(items.clone(), 3, |&(_, v)| v), vec![("b", 9), ("e", 9), ("d", 7)]);
+    assert_eq!(top_n(items.clone(), 0, |&(_, v)| v), vec![]);
+    assert_eq!(top_n(items, 10, |&(_, v)| v).len(), 5);
+}
+
+#[test]
+fn multiple_keys_sort_like_report3s_comparator() {
+    // (funding year, average savings, label); "b" and "f" tie on both keys.
+    type Row = (i32, f64, &'static str);
+    let rows: Vec<Row> = vec![(2022, 10.0, "a"), (2021, -5.0, "b"), (2023, 0.0, "c"), (2021, 40.0, "d"), (2022, 75.5, "e"), (2021, -5.0, "f")];
+
+    let mut by_hand = rows.clone();
+    by_hand.sort_by(|a, b| match a.0.cmp(&b.0) {
+        std::cmp::Ordering::Equal => b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal),
+        ordering => ordering,
+    });
+    let mut by_keys = rows;
+    let keys: [fn(&Row) -> OrdF64; 2] = [|r| OrdF64(f64::from(r.0)), |r| OrdF64(-r.1)];
+    sort_by_multiple_keys(&mut by_keys, &keys);
+
+    assert_eq!(by_keys, by_hand);
+    let labels: Vec<&str> = by_keys.iter().map(|r| r.2).collect();
+    assert_eq!(labels, vec!["d", "b", "f", "e", "a", "c"]);
+}
+
+#[test]
+fn ord_f64_equality_agrees_with_its_order() {
+    let pairs = [(f64::NAN, f64::NAN), (-0.0, 0.0), (1.5, 1.5), (1.0, 2.0)];
+    for (a, b) in pairs {
+        assert_eq!(OrdF64(a) == OrdF64(b), OrdF64(a).cmp(&OrdF64(b)).is_eq(), "{} vs {}", a, b);
+    }
+    assert!(OrdF64(f64::NAN) == OrdF64(f64::NAN));
+    assert!(OrdF64(-0.0) != OrdF64(0.0));
+}
+
+#[test]
+fn report1_ranks_regions_by_efficiency() {
+    let records = vec![
+        project("Region A", 100.0, 90.0).delay(10).build(),
+        project("Region A", 200.0, 150.0).delay(50).build(),
+        project("Region B", 100.0, 120.0).island("Visayas").delay(20).build(),
+    ];
+
+    let rows = generate_report1(&records, 0);
+    assert_eq!(column(&rows, "Region"), vec!["Region A", "Region B"]);
+    assert_eq!(column(&rows, "Rank"), vec!["1", "2"]);
+
+    let a = &rows[0];
+    assert_eq!(cell(a, "TotalBudget"), "300");
+    assert_eq!(cell(a, "MinContractCost"), "90");
+    assert_eq!(cell(a, "MaxContractCost"), "150");
+    assert_eq!(cell(a, "MedianSavings"), "30.00");
+    assert_eq!(cell(a, "AvgDelay"), "30.00");
+    assert_eq!(cell(a, "HighDelayPct"), "50.00");
+    assert_eq!(cell(a, "EfficiencyScore"), "100.00");
+
+    // Negative savings clamp the score at zero.
+    assert_eq!(cell(&rows[1], "MainIsland"), "Visayas");
+    assert_eq!(cell(&rows[1], "EfficiencyScore"), "0.00");
+    assert_eq!(cell(&generate_report1(&records, 2)[0], "TotalBudget"), "300.00");
+}
+
+// `count` on-time projects for one contractor, each costing half of its budget.
+fn contractor_projects(name: &str, count: usize, cost: f64) -> Vec<ProcessedRecord> {
+    (0..count).map(|_| project("R", cost * 2.0, cost).contractor(name).delay(0).build()).collect()
+}
+
+#[test]
+fn report2_ranks_contractors_by_total_cost() {
+    let mut records = contractor_projects("Alpha", 5, 100.0);
+    records.extend(contractor_projects("Beta", 5, 200.0));
+    records.extend(contractor_projects("Small", 4, 1_000.0));
+    records.extend(contractor_projects("Unknown", 6, 500.0));
+
+    let rows = generate_report2(&records, 0, false, 0);
+    assert_eq!(column(&rows, "Contractor"), vec!["Beta", "Alpha"]);
+    assert_eq!(cell(&rows[0], "TotalCost"), "1000");
+    assert_eq!(cell(&rows[0], "NumProjects"), "5");
+    assert_eq!(cell(&rows[0], "TotalSavings"), "1000");
+    assert_eq!(cell(&rows[0], "ReliabilityIndex"), "100.00");
+    assert_eq!(cell(&rows[0], "RiskFlag"), "Low Risk");
+
+    let top = generate_report2(&records, 1, true, 0);
+    assert_eq!(column(&top, "Contractor"), vec!["Unknown (aggregated, unreliable)"]);
+}
+
+#[test]
+fn drilldown_lists_every_project_of_one_contractor() {
+    let mut records = vec![
+        project("R", 300.0, 240.0).contractor("Small").year(2022).province("Cebu").delay(45).build(),
+        project("R", 100.0, 80.0).contractor("Small").year(2021).province("Leyte").build(),
+        project("R", 100.0, 80.0).contractor("Small").year(2021).province("Bohol").delay(45).build(),
+    ];
+    records.extend(contractor_projects("Smaller Co", 2, 100.0));
+
+    // Report 2 drops contractors with fewer than five projects; the drill-down keeps them.
+    let (rows, reliability) = generate_contractor_drilldown(&records, "Small");
+    assert_eq!(column(&rows, "Province"), vec!["Bohol", "Leyte", "Cebu"]);
+    assert_eq!(column(&rows, "DelayDays"), vec!["45", "N/A", "45"]);
+    assert_eq!(cell(&rows[2], "CostSavings"), "60.00");
+    // 25% of the cost saved, halved by the 45-day average delay.
+    assert_eq!(format_number(reliability, 2), "12.50");
+}
+
+#[test]
+fn report3_tracks_year_over_year_savings() {
+    let records = vec![
+        project("R", 100.0, 90.0).year(2021).build(),
+        project("R", 100.0, 110.0).year(2021).build(),
+        project("R", 100.0, 70.0).year(2022).build(),
+        project("R", 100.0, 50.0).year(2022).work("Seawall").build(),
+    ];
+
+    let rows = generate_report3(&records, 3);
+    assert_eq!(column(&rows, "FundingYear"), vec!["2021", "2022", "2022"]);
+    assert_eq!(column(&rows, "TypeOfWork"), vec!["Dike", "Seawall", "Dike"]);
+
+    let dike_2021 = &rows[0];
+    assert_eq!(cell(dike_2021, "AvgSavings"), "0.00");
+    assert_eq!(cell(dike_2021, "OverrunRate"), "50.00");
+    assert_eq!(cell(dike_2021, "OverrunSeverity"), "Moderate");
+    // A zero 2021 baseline leaves the change at 0.
+    assert_eq!(cell(&rows[2], "YoYChange"), "0.00");
+
+    let shifted = vec![
+        project("R", 100.0, 90.0).year(2021).build(),
+        project("R", 100.0, 80.0).year(2022).build(),
+    ];
+    let rows = generate_report3(&shifted, 3);
+    assert_eq!(cell(&rows[1], "YoYChange"), "100.00");
+}
+
+#[test]
+fn report3_smooths_savings_per_type_of_work() {
+    let records = vec![
+        project("R", 100.0, 90.0).year(2021).build(),
+        project("R", 100.0, 70.0).year(2022).build(),
+        project("R", 100.0, 50.0).year(2022).work("Seawall").build(),
+        project("R", 100.0, 60.0).year(2023).work("Seawall").build(),
+        project("R", 100.0, 100.0).year(2023).build(),
+    ];
+
+    let rows = generate_report3(&records, 2);
+    let moving_avg = |year: &str, work: &str| {
+        let row = rows.iter().find(|r| cell(*r, "FundingYear") == year && cell(*r, "TypeOfWork") == work).unwrap();
+        cell(row, "MovingAvgSavings")
+    };
+    assert_eq!(moving_avg("2021", "Dike"), "N/A");
+    assert_eq!(moving_avg("2022", "Dike"), "20.00");
+    assert_eq!(moving_avg("2023", "Dike"), "15.00");
+    assert_eq!(moving_avg("2022", "Seawall"), "N/A");
+    assert_eq!(moving_avg("2023", "Seawall"), "45.00");
+
+    let rows = generate_report3(&records, 3);
+    assert_eq!(column(&rows, "MovingAvgSavings").iter().filter(|v| *v != "N/A").count(), 1);
+}
+
+#[test]
+fn report3_moving_average_skips_a_missing_year() {
+    let records = vec![
+        project("R", 100.0, 90.0).year(2021).build(),
+        project("R", 100.0, 70.0).year(2023).build(),
+        project("R", 100.0, 50.0).year(2024).build(),
+    ];
+
+    let rows = generate_report3(&records, 2);
+    let moving_avg = |year: &str| cell(rows.iter().find(|r| cell(*r, "FundingYear") == year).unwrap(), "MovingAvgSavings");
+    assert_eq!(moving_avg("2021"), "N/A");
+    assert_eq!(moving_avg("2023"), "N/A");
+    assert_eq!(moving_avg("2024"), "40.00");
+}
+
+#[test]
+fn report6_rolls_up_by_island() {
+    let records = vec![
+        project("R", 100.0, 80.0).island("Visayas").delay(10).build(),
+        project("R", 300.0, 250.0).island("Luzon").delay(20).build(),
+        project("R", 200.0, 150.0).island("Luzon").delay(40).build(),
+        project("R", 50.0, 60.0).island(" ").build(),
+    ];
+
+    let rows = generate_report6(&records, 0);
+    assert_eq!(column(&rows, "MainIsland"), vec!["Luzon", "Visayas", "Unspecified"]);
+    let luzon = &rows[0];
+    assert_eq!(cell(luzon, "TotalBudget"), "500");
+    assert_eq!(cell(luzon, "TotalContractCost"), "400");
+    assert_eq!(cell(luzon, "TotalSavings"), "100");
+    assert_eq!(cell(luzon, "MedianSavings"), "50.00");
+    assert_eq!(cell(luzon, "NumProjects"), "2");
+    assert_eq!(cell(luzon, "AvgDelay"), "30.00");
+    assert_eq!(cell(&rows[2], "TotalSavings"), "-10");
+}
+
+#[test]
+fn report6_estimates_missing_completion_dates() {
+    let mut unfinished = project("R", 100.0, 90.0).island("Mindanao").build();
+    unfinished.start_date = Some(date("2021-06-01"));
+    let records = vec![
+        project("R", 100.0, 90.0).island("Mindanao").delay(20).build(),
+        project("R", 100.0, 90.0).island("Mindanao").delay(40).build(),
+        unfinished,
+        project("R", 100.0, 90.0).island("Visayas").build(),
+    ];
+
+    let rows = generate_report6(&records, 0);
+    // The unfinished project started last; 2021-06-01 plus the 30-day average delay.
+    assert_eq!(cell(&rows[0], "EstimatedCompletion"), "2021-07-01");
+    // No dates at all leaves the cell blank.
+    assert_eq!(cell(&rows[1], "EstimatedCompletion"), "");
+}
+
+#[test]
+fn report8_orders_by_year_then_island() {
+    let records = vec![
+        project("R", 400.0, 300.0).year(2022).island("Luzon").build(),
+        project("R", 200.0, 190.0).year(2021).island("Visayas").build(),
+        project("R", 100.0, 100.0).year(2021).island("Luzon").build(),
+        project("R", 100.0, 100.0).year(2022).island("Luzon").build(),
+    ];
+
+    let rows = generate_report8(&records, 0);
+    assert_eq!(column(&rows, "FundingYear"), vec!["2021", "2021", "2022"]);
+    assert_eq!(column(&rows, "MainIsland"), vec!["Luzon", "Visayas", "Luzon"]);
+    let luzon_2022 = &rows[2];
+    assert_eq!(cell(luzon_2022, "TotalApprovedBudget"), "500");
+    assert_eq!(cell(luzon_2022, "TotalSavings"), "100");
+    assert_eq!(cell(luzon_2022, "SavingsPct"), "20.00");
+    assert_eq!(cell(luzon_2022, "NumProjects"), "2");
+}
+
+#[test]
+fn report9_groups_by_year_and_start_quarter() {
+    let started = |year: i32, start: &str, cost: f64| {
+        let mut record = project("R", 100.0, cost).year(year).delay(20).build();
+        record.start_date = Some(date(start));
+        record
+    };
+    let mut undated = project("R", 100.0, 100.0).build();
+    undated.start_date = None;
+    let records = vec![
+        started(2022, "2022-11-30", 150.0),
+        started(2021, "2021-12-01", 90.0),
+        started(2022, "2022-02-14", 90.0),
+        started(2022, "2022-10-01", 90.0),
+        undated,
+    ];
+
+    let rows = generate_report9(&records, 0);
+    assert_eq!(column(&rows, "FundingYear"), vec!["2021", "2022", "2022"]);
+    assert_eq!(column(&rows, "Quarter"), vec!["Q4", "Q1", "Q4"]);
+    let q4_2022 = &rows[2];
+    assert_eq!(cell(q4_2022, "NumProjects"), "2");
+    assert_eq!(cell(q4_2022, "TotalBudget"), "200");
+    assert_eq!(cell(q4_2022, "AvgDelay"), "20.00");
+    assert_eq!(cell(q4_2022, "OverrunRate"), "50.00");
+}
+
+#[test]
+fn every_generator_handles_no_records() {
+    assert!(generate_report1(&[], 0).is_empty());
+    assert!(generate_report2(&[], 0, true, 0).is_empty());
+    assert!(generate_report3(&[], 3).is_empty());
+    assert!(generate_report6(&[], 0).is_empty());
+    assert!(generate_report8(&[], 0).is_empty());
+    assert!(generate_report9(&[], 0).is_empty());
+    assert!(generate_contractor_drilldown(&[], "Alpha").0.is_empty());
+
+    // Report 2 also comes out empty when no contractor reaches five projects.
+    assert!(generate_report2(&contractor_projects("Small", 4, 100.0), 0, false, 0).is_empty());
+}
+
+#[test]
+fn summary_of_no_records_is_marked_empty() {
+    let empty = generate_summary(&[], 2021, 2023);
+    assert_eq!(empty["status"], "empty");
+    assert_eq!(empty["total_projects"], 0);
+    for key in ["global_avg_delay", "delay_percentiles", "by_year", "total_savings", "negative_delay_count"] {
+        assert!(empty[key].is_null(), "{} should be null", key);
+    }
+
+    let summary = generate_summary(&[project("R", 100.0, 80.0).build()], 2021, 2023);
+    assert_eq!(summary["status"], "ok");
+    assert_eq!(summary["total_savings"], 20.0);
+
+    // The imputation strategy describes the method, so even an empty summary keeps it.
+    assert_eq!(summary["imputation_strategy"]["method"], "median");
+    assert_eq!(summary["imputation_strategy"]["outputs"]["imputed"], "cleaned_records_imputed.json");
+    assert_eq!(empty["imputation_strategy"], summary["imputation_strategy"]);
+}
+
+#[test]
+fn empty_reports_are_skipped_unless_asked_for() {
+    let dir = std::env::temp_dir().join(format!("dpwh_empty_report_{}", std::process::id()));
+    let rows = generate_report6(&[], 0);
+    let total = build_total_row(&rows, "MainIsland", "TOTAL".to_string(), &["NumProjects"]);
+    let preview = PreviewConfig { quiet: true, ..PreviewConfig::from_env() };
+    let mut output = OutputSettings::default();
+
+    write_report(&dir, "report6.csv", &rows, "Report 6", &preview, &output, &total).unwrap();
+    write_html_page(&dir, "report6.html", &rows, &total, "Report 6", &output).unwrap();
+    let skipped = dir.exists() && fs::read_dir(&dir).unwrap().count() > 0;
+
+    output.write_empty = true;
+    write_report(&dir, "report6.csv", &rows, "Report 6", &preview, &output, &total).unwrap();
+    write_html_page(&dir, "report6.html", &rows, &total, "Report 6", &output).unwrap();
+    let csv = fs::read_to_string(dir.join("report6.csv")).unwrap();
+    let html_written = dir.join("report6.html").exists();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(!skipped);
+    assert_eq!(csv.lines().next(), Some(Report6Temp::HEADERS.join(",").as_str()));
+    assert_eq!(csv.lines().nth(1), Some("TOTAL,-,-,-,-,0,-,-"));
+    assert!(html_written);
+}
+
+// Every column a report can be sorted by, checked against the raw values in its JSON form.
+fn assert_sortable<R: ReportRecord>(rows: &[R]) {
+    for row in rows {
+        assert_eq!(row.sort_values().len(), R::HEADERS.len());
+    }
+}
+
+#[test]
+fn every_report_column_has_a_raw_value_to_sort_on() {
+    let records = vec![project("R", 100.0, 80.0).delay(10).build(), project("R", 100.0, 80.0).contractor("C").delay(10).build()];
+    let mut five = contractor_projects("Alpha", 5, 100.0);
+    five.extend(records.clone());
+    assert_sortable(&generate_report1(&records, 0));
+    assert_sortable(&generate_report2(&five, 0, false, 0));
+    assert_sortable(&generate_report3(&records, 3));
+    assert_sortable(&generate_report6(&records, 0));
+    assert_sortable(&generate_report8(&records, 0));
+    assert_sortable(&generate_report9(&records, 0));
+    assert_eq!(report_headers("6"), Some(Report6Temp::HEADERS));
+    assert_eq!(report_headers("4"), None);
+}
+
+#[test]
+fn sort_specs_name_a_real_column() {
+    let sort = ReportSort::parse("report1=totalbudget:DESC").unwrap();
+    assert_eq!(sort, ReportSort { report: "1".to_string(), column: "TotalBudget".to_string(), descending: true });
+    assert_eq!(sort.to_string(), "report1=TotalBudget:desc");
+    assert!(!ReportSort::parse("2=AvgDelay").unwrap().descending);
+
+    assert!(ReportSort::parse("report1=Contractor").unwrap_err().contains("columns: Rank, Region"));
+    assert!(ReportSort::parse("report4=Region").is_err());
+    assert!(ReportSort::parse("report1=Region:up").is_err());
+    assert!(ReportSort::parse("TotalBudget").is_err());
+    assert_eq!(parse_sort_list("report1=Region; report6=NumProjects:desc").unwrap().len(), 2);
+}
+
+#[test]
+fn rows_sort_on_raw_values_and_keep_the_default_order_otherwise() {
+    let records = vec![
+        project("Region A", 950.0, 900.0).delay(10).build(),
+        project("Region B", 1_200.0, 1_000.0).year(2021).delay(10).build(),
+        project("Region B", 100.0, 100.0).year(2023).delay(10).build(),
+        project("Region C", 50.0, 10.0).delay(10).build(),
+    ];
+    let ranked = |rows: &[Report1Temp]| -> Vec<(String, String)> {
+        let mut pairs: Vec<_> = column(rows, "Region").into_iter().zip(column(rows, "Rank")).collect();
+        pairs.sort();
+        pairs
+    };
+    let default_rows = generate_report1(&records, 0);
+
+    let mut rows = generate_report1(&records, 0);
+    apply_report_sort(&mut rows, &[], "1");
+    assert_eq!(column(&rows, "Region"), column(&default_rows, "Region"));
+
+    // As text, "1300" would sort before "950".
+    let by_budget = parse_sort_list("report1=TotalBudget:desc").unwrap();
+    apply_report_sort(&mut rows, &by_budget, "1");
+    assert_eq!(column(&rows, "TotalBudget"), vec!["1300", "950", "50"]);
+    assert_eq!(ranked(&rows), ranked(&default_rows));
+
+    sort_report_rows(&mut rows, &ReportSort::parse("1=Region").unwrap());
+    assert_eq!(column(&rows, "Region"), vec!["Region A", "Region B", "Region C"]);
+
+    // Regions funded in a single year have no growth rate; they stay last either way.
+    for direction in ["asc", "desc"] {
+        sort_report_rows(&mut rows, &ReportSort::parse(&format!("1=RegionCagr:{}", direction)).unwrap());
+        assert_eq!(column(&rows, "Region")[0], "Region B");
+    }
+}
+
+// Sorts `rows` on `column_name` and returns that column, formatted.
+fn sorted_column<R: ReportRecord>(mut rows: Vec<R>, report: &str, column_name: &str, direction: &str) -> Vec<String> {
+    sort_report_rows(&mut rows, &ReportSort::parse(&format!("{}={}:{}", report, column_name, direction)).unwrap());
+    column(&rows, column_name)
+}
+
+#[test]
+fn every_report_sorts_on_a_numeric_column_by_value() {
+    // As text, descending would put "950" before "50" before "1200".
+    let records = vec![
+        project("Region A", 950.0, 900.0).island("Luzon").year(2021).work("Dike").delay(10).build(),
+        project("Region B", 1_200.0, 1_000.0).island("Visayas").year(2022).work("Seawall").delay(10).build(),
+        project("Region C", 50.0, 10.0).island("Mindanao").year(2023).work("Revetment").delay(10).build(),
+    ];
+    let mut contractors = contractor_projects("Alpha", 5, 190.0);
+    contractors.extend(contractor_projects("Beta", 5, 240.0));
+    contractors.extend(contractor_projects("Gamma", 5, 10.0));
+
+    assert_eq!(sorted_column(generate_report1(&records, 0), "1", "TotalBudget", "desc"), vec!["1200", "950", "50"]);
+    assert_eq!(sorted_column(generate_report2(&contractors, 0, false, 0), "2", "TotalCost", "asc"), vec!["50", "950", "1200"]);
+    assert_eq!(sorted_column(generate_report3(&records, 3), "3", "AvgSavings", "desc"), vec!["200.00", "50.00", "40.00"]);
+    assert_eq!(sorted_column(generate_report6(&records, 0), "6", "TotalBudget", "desc"), vec!["1200", "950", "50"]);
+    assert_eq!(sorted_column(generate_report8(&records, 0), "8", "TotalApprovedBudget", "desc"), vec!["1200", "950", "50"]);
+    assert_eq!(sorted_column(generate_report9(&records, 0), "9", "TotalBudget", "desc"), vec!["1200", "950", "50"]);
+}
+
+#[test]
+fn report_headers_keep_their_csv_order() {
+    assert_eq!(
+        Report1Temp::HEADERS,
+        [
+            "Rank", "Region", "MainIsland", "TotalBudget", "MinContractCost", "MaxContractCost", "MedianSavings",
+            "P25Savings", "P75Savings", "AvgDelay", "DelayStdDev", "P90Delay", "HighDelayPct", "EfficiencyScore", "RegionCagr",
+        ]
+    );
+    assert_eq!(
+        Report2Temp::HEADERS,
+        ["Rank", "Contractor", "TotalCost", "NumProjects", "AvgDelay", "TotalSavings", "ReliabilityIndex", "RiskFlag"]
+    );
+    assert_eq!(
+        Report3Temp::HEADERS,
+        [
+            "FundingYear", "TypeOfWork", "TotalProjects", "AvgSavings", "SavingsStdDev", "OverrunRate", "OverrunSeverity",
+            "YoYChange", "MovingAvgSavings",
+        ]
+    );
+    assert_eq!(
+        Report6Temp::HEADERS,
+        [
+            "MainIsland", "TotalBudget", "TotalContractCost", "TotalSavings", "MedianSavings", "NumProjects", "AvgDelay",
+            "EstimatedCompletion",
+        ]
+    );
+    assert_eq!(
+        Report8Temp::HEADERS,
+        ["FundingYear", "MainIsland", "TotalApprovedBudget", "TotalContractCost", "TotalSavings", "SavingsPct", "NumProjects"]
+    );
+    assert_eq!(Report9Temp::HEADERS, ["FundingYear", "Quarter", "NumProjects", "TotalBudget", "AvgDelay", "OverrunRate"]);
+}
+
+#[test]
+fn report_cells_are_formatted_in_header_order() {
+    let records = vec![
+        project("Region A", 1_000.5, 900.25).delay(10).build(),
+        project("Region A", 2_000.0, 1_500.0).delay(50).build(),
+    ];
+
+    let r1 = generate_report1(&records, 0);
+    assert_eq!(
+        r1[0].cells(),
+        ["1", "Region A", "Luzon", "3001", "900", "1500", "300.13", "200.19", "400.06", "30.00", "28.28", "46.00", "50.00", "100.00", "N/A"]
+    );
+
+    let r3 = generate_report3(&records, 3);
+    assert_eq!(r3[0].cells(), ["2021", "Dike", "2", "300.13", "282.67", "0.00", "None", "0.00", "N/A"]);
+
+    let r6 = generate_report6(&records, 2);
+    assert_eq!(r6[0].cells(), ["Luzon", "3000.50", "2400.25", "600.25", "300.13", "2", "30.00", "2021-02-20"]);
+
+    let r8 = generate_report8(&records, 0);
+    assert_eq!(r8[0].cells(), ["2021", "Luzon", "3001", "2400", "600", "20.00", "2"]);
+
+    let r2 = generate_report2(&contractor_projects("Alpha", 5, 100.0), 0, false, 1);
+    assert_eq!(r2[0].cells(), ["1", "Alpha", "500.0", "5", "0.00", "500.0", "100.00", "Low Risk"]);
+}
+
+#[test]
+fn total_row_sums_the_chosen_columns() {
+    let records = vec![
+        project("R", 100.0, 80.0).island("Visayas").build(),
+        project("R", 300.0, 250.0).island("Luzon").build(),
+    ];
+    let rows = generate_report6(&records, 0);
+    let total = build_total_row(&rows, "MainIsland", "TOTAL".to_string(), &["TotalBudget", "NumProjects"]);
+    assert_eq!(total, ["TOTAL", "400", "-", "-", "-", "2", "-", "-"]);
+}
+
+#[test]
+fn json_export_keeps_raw_numbers() {
+    let rows = generate_report3(&[project("R", 100.0, 110.0).build()], 3);
+    let json = serde_json::to_value(&rows).unwrap();
+    assert_eq!(json[0]["TypeOfWork"], "Dike");
+    assert_eq!(json[0]["AvgSavings"], -10.0);
+    assert_eq!(json[0]["OverrunSeverity"], "Moderate");
+    assert_eq!(json[0]["YoYChange"], 0.0);
+
+    let json = serde_json::to_value(generate_report6(&[project("R", 1.0, 1.0).build()], 2)).unwrap();
+    assert!(json[0].get("BudgetDecimals").is_none());
+}
+
+#[test]
+fn report_writer_puts_headers_first_and_quotes_commas() {
+    let records = vec![
+        project("R", 300.0, 250.0).island("Luzon").build(),
+        project("R", 100.0, 80.0).island("Mindanao, Sulu").build(),
+    ];
+    let rows = generate_report6(&records, 0);
+    let total = build_total_row(&rows, "MainIsland", "TOTAL".to_string(), &["NumProjects"]);
+
+    let mut buffer = Vec::new();
+    write_report_to_writer(&mut buffer, &rows, &total, Delimiter::Comma, false).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], Report6Temp::HEADERS.join(","));
+    assert_eq!(lines[1], "Luzon,300,250,50,50.00,1,0.00,");
+    assert_eq!(lines[2], "\"Mindanao, Sulu\",100,80,20,20.00,1,0.00,");
+    assert_eq!(lines[3], "TOTAL,-,-,-,-,2,-,-");
+
+    let mut buffer = Vec::new();
+    write_report_to_writer(&mut buffer, &rows, &[], Delimiter::Tab, true).unwrap();
+    assert!(buffer.starts_with(b"\xEF\xBB\xBFMainIsland\tTotalBudget"));
+    let text = String::from_utf8(buffer).unwrap();
+    assert_eq!(text.matches("\r\n").count(), 3);
+    assert!(text.contains("\nMindanao, Sulu\t100"));
+}
+
+#[test]
+fn output_format_picks_the_report_files() {
+    assert_eq!(OutputFormat::parse(" JSON "), Some(OutputFormat::Json));
+    assert_eq!(OutputFormat::parse("xml"), None);
+
+    let names = |format| -> Vec<String> {
+        report_outputs(&Config::default(), std::path::Path::new("out"), "1", None, format)
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect()
+    };
+    assert_eq!(names(OutputFormat::Json), ["report1_regional_efficiency.json", "report1.html"]);
+    assert_eq!(
+        names(OutputFormat::Csv),
+        ["report1_regional_efficiency.csv", "report1_regional_efficiency.tsv", "report1.html"]
+    );
+    assert_eq!(names(OutputFormat::Both).len(), 4);
+}
+
+#[test]
+fn pipeline_json_carries_stages_and_record_counts() {
+    let mut pipeline = Pipeline::new();
+    pipeline.record("validate", 10, 7, std::time::Instant::now());
+    pipeline.record_counts.extend([("raw", 10), ("valid", 7)]);
+    let stats = pipeline.to_json();
+
+    assert_eq!(stats["stages"][0]["name"], "validate");
+    assert_eq!(stats["stages"][0]["output_count"], 7);
+    assert_eq!(
+        stats["record_counts"],
+        serde_json::json!([{ "stage": "raw", "records": 10 }, { "stage": "valid", "records": 7 }])
+    );
+}
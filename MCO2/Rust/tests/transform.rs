@@ -0,0 +1,264 @@
+mod common;
+
+use dpwh::model::{DatasetMedians, OverrunSeverity};
+use dpwh::transform::{
+    add_derived_fields, calculate_completion_delay, calculate_cost_savings, compute_dataset_medians, compute_overrun_severity, delay_mean_and_std_dev, detect_coordinate_conflicts,
+    detect_severe_delays, estimate_completion_date, extract_quarter, fill_missing_with_medians, find_contractors, flag_negative_delays, impute_coordinates, interpolate_missing_date,
+    reconcile_cross_file_duplicates, remove_duplicates, strip_derived_fields,
+};
+
+use common::{date, project};
+
+#[test]
+fn savings_is_budget_minus_cost() {
+    assert_eq!(calculate_cost_savings(1_000.0, 750.0), 250.0);
+    assert_eq!(calculate_cost_savings(1_000.0, 1_200.0), -200.0);
+}
+
+#[test]
+fn delay_needs_both_dates() {
+    let start = Some(date("2022-01-10"));
+    let end = Some(date("2022-03-11"));
+    assert_eq!(calculate_completion_delay(start, end), Some(60));
+    assert_eq!(calculate_completion_delay(start, None), None);
+    assert_eq!(calculate_completion_delay(None, end), None);
+}
+
+#[test]
+fn quarters_follow_the_calendar() {
+    assert_eq!(extract_quarter(date("2022-01-01")), 1);
+    assert_eq!(extract_quarter(date("2022-03-31")), 1);
+    assert_eq!(extract_quarter(date("2022-04-01")), 2);
+    assert_eq!(extract_quarter(date("2022-09-30")), 3);
+    assert_eq!(extract_quarter(date("2022-12-31")), 4);
+}
+
+#[test]
+fn interpolated_dates_round_to_the_nearest_day() {
+    let (start, end) = (date("2022-01-01"), date("2022-01-11"));
+    assert_eq!(interpolate_missing_date(start, end, 0.0), start);
+    assert_eq!(interpolate_missing_date(start, end, 1.0), end);
+    assert_eq!(interpolate_missing_date(start, end, 0.25), date("2022-01-04"));
+    assert_eq!(interpolate_missing_date(start, end, 1.5), date("2022-01-16"));
+}
+
+#[test]
+fn completion_is_estimated_only_when_missing() {
+    let finished = project("R", 1.0, 1.0).delay(10).build();
+    assert_eq!(estimate_completion_date(&finished, 99.0), Some(date("2021-01-11")));
+
+    let mut unfinished = finished.clone();
+    unfinished.actual_completion_date = None;
+    assert_eq!(estimate_completion_date(&unfinished, 45.4), Some(date("2021-02-15")));
+
+    unfinished.start_date = None;
+    assert_eq!(estimate_completion_date(&unfinished, 45.0), None);
+}
+
+#[test]
+fn derived_fields_are_filled_in() {
+    let record = project("Region I", 500.0, 450.0).delay(45).build();
+    assert_eq!(record.cost_savings, 50.0);
+    assert_eq!(record.completion_delay_days, Some(45));
+    assert!(!record.coordinates_imputed);
+}
+
+#[test]
+fn overrun_severity_thresholds() {
+    let severity = |cost| compute_overrun_severity(&project("R", 100.0, cost).build());
+    assert!(severity(90.0) == OverrunSeverity::None);
+    assert!(severity(105.0) == OverrunSeverity::Minor);
+    assert!(severity(120.0) == OverrunSeverity::Moderate);
+    assert!(severity(120.5) == OverrunSeverity::Severe);
+}
+
+#[test]
+fn negative_delays_are_counted_and_optionally_nulled() {
+    let records = vec![project("R", 1.0, 1.0).delay(-3).build(), project("R", 1.0, 1.0).delay(3).build()];
+
+    let (kept, flagged) = flag_negative_delays(records.clone(), false);
+    assert_eq!(flagged, 1);
+    assert_eq!(kept[0].completion_delay_days, Some(-3));
+
+    let (nulled, flagged) = flag_negative_delays(records, true);
+    assert_eq!(flagged, 1);
+    assert_eq!(nulled[0].completion_delay_days, None);
+    assert_eq!(nulled[1].completion_delay_days, Some(3));
+}
+
+#[test]
+fn severe_delays_stand_two_std_devs_above_the_mean() {
+    // Mean 31.8 days, std dev 72.3: anything past about 176 days is severe.
+    let mut records = vec![project("R", 1.0, 1.0).build(), project("R", 1.0, 1.0).delay(200).build(), project("R", 1.0, 1.0).delay(300).build()];
+    records.extend((0..20).map(|_| project("R", 1.0, 1.0).delay(10).build()));
+
+    let (mean, std_dev) = delay_mean_and_std_dev(&records);
+    assert!((mean - 700.0 / 22.0).abs() < 1e-9);
+    assert!((std_dev - 72.28).abs() < 0.01);
+    assert_eq!(detect_severe_delays(&records), vec![2, 1]);
+
+    let steady: Vec<_> = (0..5).map(|_| project("R", 1.0, 1.0).delay(40).build()).collect();
+    assert!(detect_severe_delays(&steady).is_empty());
+    assert!(detect_severe_delays(&records[..1]).is_empty());
+}
+
+#[test]
+fn stripping_derived_fields_recovers_the_cleaned_record() {
+    let cleaned = project("R", 100.0, 80.0).delay(30).coords(Some(14.0), None).cleaned();
+    let processed = add_derived_fields(cleaned.clone());
+    assert_eq!(strip_derived_fields(&processed), cleaned);
+
+    // Province averages filled in while loading are not part of the cleaned data.
+    let neighbour = project("R", 100.0, 80.0).coords(Some(15.0), Some(121.0)).build();
+    let imputed = impute_coordinates(vec![processed, neighbour]);
+    assert!(imputed[0].coordinates_imputed);
+    assert_eq!(strip_derived_fields(&imputed[0]).project_longitude, None);
+}
+
+#[test]
+fn medians_come_only_from_complete_records() {
+    let records = vec![
+        project("R", 1.0, 1.0).delay(10).coords(Some(14.0), Some(121.0)).cleaned(),
+        project("R", 1.0, 1.0).delay(20).coords(Some(15.0), Some(122.0)).cleaned(),
+        project("R", 1.0, 1.0).delay(40).coords(Some(17.0), Some(124.0)).cleaned(),
+        // Incomplete: no dates, or no longitude.
+        project("R", 1.0, 1.0).coords(Some(99.0), Some(99.0)).cleaned(),
+        project("R", 1.0, 1.0).delay(500).coords(Some(99.0), None).cleaned(),
+    ];
+    let medians = compute_dataset_medians(&records);
+    assert_eq!(medians.latitude, Some(15.0));
+    assert_eq!(medians.longitude, Some(122.0));
+    assert_eq!(medians.delay_days, Some(20.0));
+    assert_eq!(medians.complete_records, 3);
+
+    assert_eq!(compute_dataset_medians(&records[3..]), DatasetMedians::default());
+}
+
+#[test]
+fn missing_fields_are_filled_with_medians() {
+    let medians = DatasetMedians { latitude: Some(15.0), longitude: Some(122.0), delay_days: Some(20.0), complete_records: 3 };
+    let complete = project("R", 1.0, 1.0).delay(10).coords(Some(14.0), Some(121.0)).cleaned();
+    let mut no_completion = project("R", 1.0, 1.0).delay(10).coords(None, Some(121.0)).cleaned();
+    no_completion.actual_completion_date = None;
+    let mut no_start = project("R", 1.0, 1.0).delay(10).cleaned();
+    no_start.start_date = None;
+    let no_dates = project("R", 1.0, 1.0).cleaned();
+
+    let imputed = fill_missing_with_medians(vec![complete.clone(), no_completion, no_start, no_dates], &medians);
+    assert_eq!(imputed[0], complete);
+    assert_eq!(imputed[1].project_latitude, Some(15.0));
+    assert_eq!(imputed[1].project_longitude, Some(121.0));
+    assert_eq!(imputed[1].actual_completion_date, Some(date("2021-01-21")));
+    assert_eq!(imputed[2].start_date, Some(date("2020-12-22")));
+    assert_eq!((imputed[3].start_date, imputed[3].actual_completion_date), (None, None));
+    assert_eq!(imputed[3].project_longitude, Some(122.0));
+
+    // Without medians nothing changes.
+    let untouched = project("R", 1.0, 1.0).cleaned().with_defaults(&DatasetMedians::default());
+    assert_eq!(untouched, project("R", 1.0, 1.0).cleaned());
+}
+
+#[test]
+fn duplicates_keep_first_occurrence() {
+    let first = project("Region I", 100.0, 90.0).delay(5).contractor("A").build();
+    let mut repeat = first.clone();
+    repeat.region = "Region II".to_string();
+    let other = project("Region I", 200.0, 90.0).delay(5).contractor("A").build();
+
+    let (kept, removed) = remove_duplicates(vec![first, repeat, other]);
+    assert_eq!(kept.len(), 2);
+    assert_eq!(kept[0].region, "Region I");
+    assert_eq!(removed.len(), 1);
+    assert_eq!(removed[0].region, "Region II");
+}
+
+#[test]
+fn cross_file_duplicates_prefer_completed_then_later_files() {
+    let files = ["q2.csv".to_string(), "q4.csv".to_string()];
+    let mut open_in_q4 = project("Region I", 100.0, 90.0).file("q4.csv").build();
+    open_in_q4.start_date = Some(date("2022-05-01"));
+    let done_in_q2 = project("Region I", 100.0, 90.0).delay(30).file("q2.csv").build();
+
+    // A completion date beats a later file.
+    let (kept, collapsed) = reconcile_cross_file_duplicates(vec![done_in_q2.clone(), open_in_q4.clone()], &files);
+    assert_eq!(kept, vec![done_in_q2.clone()]);
+    assert_eq!(collapsed, vec![open_in_q4]);
+
+    // With both completed, the later file wins whatever the row order.
+    let done_in_q4 = project("Region I", 100.0, 90.0).delay(45).file("q4.csv").build();
+    for rows in [vec![done_in_q2.clone(), done_in_q4.clone()], vec![done_in_q4.clone(), done_in_q2.clone()]] {
+        let (kept, collapsed) = reconcile_cross_file_duplicates(rows, &files);
+        assert_eq!(kept, vec![done_in_q4.clone()]);
+        assert_eq!(collapsed.len(), 1);
+    }
+}
+
+#[test]
+fn same_file_and_distinct_projects_are_not_reconciled() {
+    let files = ["q2.csv".to_string(), "q4.csv".to_string()];
+    let records = vec![
+        project("Region I", 100.0, 90.0).file("q2.csv").build(),
+        project("Region I", 100.0, 90.0).file("q2.csv").build(),
+        project("Region I", 200.0, 90.0).file("q4.csv").build(),
+        project("Region I", 100.0, 90.0).year(2022).file("q4.csv").build(),
+    ];
+    let (kept, collapsed) = reconcile_cross_file_duplicates(records.clone(), &files);
+    assert_eq!(kept, records);
+    assert!(collapsed.is_empty());
+}
+
+#[test]
+fn contractors_are_found_ignoring_case() {
+    let records = vec![
+        project("R", 1.0, 1.0).contractor("ACME").build(),
+        project("R", 1.0, 1.0).contractor("ACME / Beta Builders").build(),
+        project("R", 1.0, 1.0).contractor("Beta Builders").build(),
+        project("R", 1.0, 1.0).contractor("ACME").build(),
+    ];
+    assert_eq!(find_contractors(&records, "acme"), vec!["ACME"]);
+    assert_eq!(find_contractors(&records, " beta "), vec!["ACME / Beta Builders", "Beta Builders"]);
+    assert!(find_contractors(&records, "gamma").is_empty());
+}
+
+#[test]
+fn missing_coordinates_use_province_average() {
+    let records = vec![
+        project("R", 1.0, 1.0).province("Cebu").coords(Some(10.0), Some(123.0)).build(),
+        project("R", 1.0, 1.0).province("Cebu").coords(Some(11.0), Some(124.0)).build(),
+        project("R", 1.0, 1.0).province("Cebu").coords(None, Some(125.0)).build(),
+        project("R", 1.0, 1.0).province("Bohol").coords(None, None).build(),
+    ];
+
+    let imputed = impute_coordinates(records);
+    assert_eq!(imputed[2].project_latitude, Some(10.5));
+    assert_eq!(imputed[2].project_longitude, Some(125.0));
+    assert!(imputed[2].coordinates_imputed);
+    assert!(!imputed[0].coordinates_imputed);
+    // No known coordinates in the province, so nothing to average.
+    assert_eq!(imputed[3].project_latitude, None);
+    assert!(!imputed[3].coordinates_imputed);
+}
+
+#[test]
+fn shared_coordinates_across_provinces_are_conflicts() {
+    let at = |province: &str, lat: f64, lon: f64| {
+        project("R", 1.0, 1.0).province(province).coords(Some(lat), Some(lon)).build()
+    };
+    let mut imputed = at("Leyte", 10.0, 123.0);
+    imputed.coordinates_imputed = true;
+    let records = vec![
+        at("Cebu", 10.0, 123.0),
+        at("Bohol", 10.0, 123.0),
+        at("Cebu", 10.0, 123.0),
+        imputed,
+        at("Cebu", 9.5, 123.0),
+        at("Bohol", 9.5, 123.5),
+        at("Samar", 12.0, 125.0),
+        at("Samar", 12.0, 125.0),
+    ];
+
+    let conflicts = detect_coordinate_conflicts(&records);
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!((conflicts[0].latitude, conflicts[0].longitude), (10.0, 123.0));
+    assert_eq!(conflicts[0].provinces, vec![("Cebu".to_string(), 2), ("Bohol".to_string(), 1)]);
+}
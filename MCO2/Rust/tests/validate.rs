@@ -0,0 +1,144 @@
+mod common;
+
+use dpwh::config::ValidationConfig;
+use dpwh::model::ValidationLog;
+use dpwh::validate::{
+    clean_raw_record, clean_record, validate_date_flexible, validate_island_consistency, validate_number,
+    validate_number_range, validate_record,
+};
+
+use common::{date, raw_record};
+
+#[test]
+fn dates_parse_in_every_default_format() {
+    let formats = ValidationConfig::default().date_formats;
+    let spellings = [
+        // %Y-%m-%d
+        "2022-03-11",
+        "2022-3-11",
+        "  2022-03-11\t",
+        // %m/%d/%Y
+        "03/11/2022",
+        "3/11/2022",
+        // %d-%b-%Y
+        "11-Mar-2022",
+        "11-MAR-2022",
+        "11-mar-2022",
+        // %B %d, %Y
+        "March 11, 2022",
+        "march 11, 2022",
+        "MARCH 11, 2022",
+        "March 11,  2022",
+    ];
+    for text in spellings {
+        assert_eq!(validate_date_flexible(text, &formats), Some(date("2022-03-11")), "{}", text);
+    }
+}
+
+#[test]
+fn blank_and_placeholder_dates_are_missing() {
+    let formats = ValidationConfig::default().date_formats;
+    assert_eq!(validate_date_flexible("", &formats), None);
+    assert_eq!(validate_date_flexible("N/A", &formats), None);
+    assert_eq!(validate_date_flexible("2022-13-40", &formats), None);
+}
+
+#[test]
+fn numbers_accept_thousands_separators() {
+    assert_eq!(validate_number("1,234,567.50"), Some(1_234_567.5));
+    assert_eq!(validate_number(" 42 "), Some(42.0));
+    assert_eq!(validate_number("N/A"), None);
+    assert_eq!(validate_number("Clustered with Contract ID 23PD0057"), None);
+}
+
+#[test]
+fn number_range_is_inclusive() {
+    assert_eq!(validate_number_range("0.01", 0.01, 100.0), Some(0.01));
+    assert_eq!(validate_number_range("100", 0.01, 100.0), Some(100.0));
+    assert_eq!(validate_number_range("0", 0.01, 100.0), None);
+    assert_eq!(validate_number_range("100.5", 0.01, 100.0), None);
+}
+
+#[test]
+fn valid_record_cleans_to_typed_fields() {
+    let cleaned = clean_record(&raw_record(), &ValidationConfig::default()).unwrap();
+    assert_eq!(cleaned.funding_year, 2022);
+    assert_eq!(cleaned.approved_budget_for_contract, 1_000_000.0);
+    assert_eq!(cleaned.contract_cost, 950_000.0);
+    assert_eq!(cleaned.start_date, Some(date("2022-01-10")));
+    assert_eq!(cleaned.project_latitude, Some(16.5));
+}
+
+#[test]
+fn blank_contractor_and_type_become_unknown() {
+    let mut raw = raw_record();
+    raw.contractor = " ".to_string();
+    raw.type_of_work = String::new();
+    let cleaned = clean_record(&raw, &ValidationConfig::default()).unwrap();
+    assert_eq!(cleaned.contractor, "Unknown");
+    assert_eq!(cleaned.type_of_work, "Unknown");
+}
+
+#[test]
+fn invalid_record_lists_every_problem() {
+    let mut raw = raw_record();
+    raw.region = String::new();
+    raw.funding_year = "2024".to_string();
+    raw.contract_cost = "-5".to_string();
+    raw.project_latitude = "91".to_string();
+    let rules = ValidationConfig::default();
+
+    let result = validate_record(&raw, &rules);
+    assert!(!result.is_valid);
+    assert_eq!(
+        result.errors,
+        vec![
+            "Missing Region",
+            "Invalid FundingYear: 2024",
+            "ContractCost out of range: -5",
+            "ProjectLatitude out of range: 91",
+        ]
+    );
+    assert!(clean_record(&raw, &rules).is_none());
+}
+
+#[test]
+fn validation_result_display() {
+    let rules = ValidationConfig::default();
+    assert_eq!(validate_record(&raw_record(), &rules).to_string(), "Valid");
+
+    let mut raw = raw_record();
+    raw.approved_budget_for_contract = "abc".to_string();
+    assert_eq!(
+        validate_record(&raw, &rules).to_string(),
+        "  - Invalid ApprovedBudgetForContract: abc"
+    );
+}
+
+#[test]
+fn islands_follow_the_region() {
+    assert!(validate_island_consistency("Region I", "Luzon"));
+    assert!(validate_island_consistency("region vii", " VISAYAS "));
+    assert!(!validate_island_consistency("Region IV-B", "Visayas"));
+    assert!(!validate_island_consistency("National Capital Region", "Mindanao"));
+    // Regions outside the map cannot be checked.
+    assert!(validate_island_consistency("Region XX", "Mindanao"));
+}
+
+#[test]
+fn island_mismatch_warns_but_keeps_the_record() {
+    let rules = ValidationConfig::default();
+    let mut raw = raw_record();
+    raw.main_island = "Mindanao".to_string();
+
+    let result = validate_record(&raw, &rules);
+    assert!(result.is_valid);
+    assert_eq!(result.warnings, vec!["MainIsland Mindanao does not match Region I (expected Luzon)"]);
+
+    let mut log = ValidationLog::default();
+    assert!(clean_raw_record(&raw, 4, &rules, &mut log).is_some());
+    assert!(clean_raw_record(&raw_record(), 5, &rules, &mut log).is_some());
+    assert!(log.errors.is_empty());
+    assert_eq!(log.island_warnings.len(), 1);
+    assert_eq!((log.island_warnings[0].row, log.island_warnings[0].expected_island.as_str()), (5, "Luzon"));
+}